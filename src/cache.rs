@@ -0,0 +1,151 @@
+use crate::cli::{MessageFormat, Opt, ProgressFormat};
+use crate::utils::{fnv1a, fnv1a_update};
+use color_eyre::eyre::{ContextCompat, Result, WrapErr};
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Calculate a digest of the input files at `path` (a section or all files of
+/// a notebook) and the conversion options.
+pub(crate) fn digest(path: &Path, opt: &Opt) -> Result<u64> {
+    let mut files = Vec::new();
+
+    if path.extension().map_or(false, |ext| ext == "onetoc2") {
+        let notebook_dir = path.parent().wrap_err("Input file has no parent folder")?;
+        collect_files(notebook_dir, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    // Changes to the strings, note tags, redaction or replacement rules change
+    // the pages as well
+    files.extend(opt.strings.clone());
+    files.extend(opt.note_tag_map.clone());
+    files.extend(opt.redact.clone());
+    files.extend(opt.replace.clone());
+    files.extend(opt.footer.clone());
+    files.extend(opt.favicon_image.clone());
+    #[cfg(feature = "scripting")]
+    files.extend(opt.script.clone());
+
+    // Only consider the options that affect the output
+    let options = Opt {
+        input: Vec::new(),
+        cache_dir: None,
+        resume: false,
+        serve: None,
+        watch: false,
+        inventory: None,
+        merge_into: None,
+        timings: false,
+        asset_threads: 1,
+        headers: Vec::new(),
+        message_format: MessageFormat::Human,
+        progress: ProgressFormat::Bar,
+        ..opt.clone()
+    };
+
+    let mut hash = fnv1a(format!("{:?}", options).as_bytes());
+
+    for file in files {
+        hash = fnv1a_update(hash, file.to_string_lossy().as_bytes());
+        hash = hash_file(hash, &file)?;
+    }
+
+    Ok(hash)
+}
+
+/// Check whether the input at `path` has been converted before with the same
+/// `digest`, i.e. neither the input files nor the options have changed since,
+/// and its output still exists. Returns the number of pages that matched the
+/// page filter back then.
+pub(crate) fn is_fresh(cache_dir: &Path, path: &Path, digest: u64) -> Result<Option<usize>> {
+    let entry = entry_path(cache_dir, path)?;
+
+    if !entry.is_file() {
+        return Ok(None);
+    }
+
+    let cached = fs::read_to_string(entry).wrap_err("Failed to read cache entry")?;
+    let mut fields = cached.splitn(3, '\n');
+
+    let cached_digest = fields.next().unwrap_or_default();
+    let matched_pages = fields.next().and_then(|count| count.parse().ok());
+    let output = fields
+        .next()
+        .map(|output| Path::new(output.trim_end_matches('\n')));
+
+    match (matched_pages, output) {
+        (Some(matched_pages), Some(output))
+            if cached_digest == format!("{:016x}", digest) && output.exists() =>
+        {
+            Ok(Some(matched_pages))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Remember that the input at `path` has been converted to `output` with
+/// `digest`, and how many of its pages matched the page filter.
+pub(crate) fn store(
+    cache_dir: &Path,
+    path: &Path,
+    digest: u64,
+    output: &Path,
+    matched_pages: usize,
+) -> Result<()> {
+    if !cache_dir.is_dir() {
+        fs::create_dir_all(cache_dir).wrap_err("Failed to create cache directory")?;
+    }
+
+    let output = output
+        .canonicalize()
+        .wrap_err("Failed to resolve output path")?;
+
+    fs::write(
+        entry_path(cache_dir, path)?,
+        format!("{:016x}\n{}\n{}\n", digest, matched_pages, output.display()),
+    )
+    .wrap_err("Failed to write cache entry")
+}
+
+fn entry_path(cache_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let path = path
+        .canonicalize()
+        .wrap_err("Failed to resolve input path")?;
+
+    Ok(cache_dir.join(format!("{:016x}", fnv1a(path.to_string_lossy().as_bytes()))))
+}
+
+pub(crate) fn hash_file(mut hash: u64, path: &Path) -> Result<u64> {
+    let mut file = File::open(path).wrap_err("Failed to open input file")?;
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let len = file
+            .read(&mut buffer)
+            .wrap_err("Failed to read input file")?;
+
+        if len == 0 {
+            return Ok(hash);
+        }
+
+        hash = fnv1a_update(hash, &buffer[..len]);
+    }
+}
+
+pub(crate) fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use log::error;
+use std::ffi::CStr;
+use std::fs;
+use std::os::raw::{c_char, c_int};
+use std::panic;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of input files written so far, to name them uniquely.
+static INPUTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Convert a section, given as the contents of a `.one` file, to HTML.
+///
+/// `output_dir` is the directory to write the HTML files to. `options` holds
+/// additional command line options, one argument per string like `argv` (e.g.
+/// `"--lang", "de"`), terminated by a null pointer. It may be null itself.
+/// All conversions in a process must use the same options.
+///
+/// Returns 0 if the section has been converted and 1 if the conversion failed.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. `output_dir` must point to a
+/// NUL-terminated UTF-8 string. `options` (if not null) must point to an array
+/// of such strings that ends with a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn one2html_convert_section(
+    data: *const u8,
+    len: usize,
+    output_dir: *const c_char,
+    options: *const *const c_char,
+) -> c_int {
+    if data.is_null() || output_dir.is_null() {
+        return 1;
+    }
+
+    let data = std::slice::from_raw_parts(data, len);
+    let output_dir = CStr::from_ptr(output_dir).to_string_lossy().into_owned();
+    let mut args = Vec::new();
+
+    if !options.is_null() {
+        for i in 0.. {
+            let arg = *options.add(i);
+
+            if arg.is_null() {
+                break;
+            }
+
+            args.push(CStr::from_ptr(arg).to_string_lossy().into_owned());
+        }
+    }
+
+    // Panics must not unwind into the calling application
+    let result = panic::catch_unwind(|| convert_section(data, &output_dir, args))
+        .unwrap_or_else(|_| Err(eyre!("Conversion panicked")));
+
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            error!("{:?}", err);
+
+            1
+        }
+    }
+}
+
+fn convert_section(data: &[u8], output_dir: &str, options: Vec<String>) -> Result<()> {
+    // The parser only reads sections from files
+    let id = INPUTS.fetch_add(1, Ordering::SeqCst);
+    let name = format!("one2html-{}-{}.one", process::id(), id);
+    let input = std::env::temp_dir().join(name);
+    fs::write(&input, data).wrap_err("Failed to write input file")?;
+
+    let result = crate::convert_section(&input, Path::new(output_dir), options);
+    let removed = fs::remove_file(&input).wrap_err("Failed to remove input file");
+
+    // A failed conversion is more important than a leftover input file
+    result.and(removed)
+}
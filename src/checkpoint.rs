@@ -0,0 +1,62 @@
+use color_eyre::eyre::{Result, WrapErr};
+use std::collections::HashMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub(crate) const FILE_NAME: &str = ".one2html-checkpoint";
+
+fn path(output_dir: &Path) -> PathBuf {
+    output_dir.join(FILE_NAME)
+}
+
+/// Load the pages that have been completed by a previous run along with the
+/// names of the files they've written to their section's directory. The pages
+/// are identified by their paths relative to the output directory.
+pub(crate) fn load(output_dir: &Path) -> Result<HashMap<String, Vec<String>>> {
+    let path = path(output_dir);
+
+    if !path.is_file() {
+        return Ok(HashMap::new());
+    }
+
+    let data = fs::read_to_string(path).wrap_err("Failed to read checkpoint")?;
+
+    Ok(data
+        .lines()
+        .map(|line| {
+            let mut fields = line.split('\t').map(|field| field.to_string());
+            let page = fields.next().unwrap_or_default();
+
+            (page, fields.collect())
+        })
+        .collect())
+}
+
+/// Mark the page at `page_path` (relative to the output directory) as
+/// completed, along with the names of the files it has written.
+pub(crate) fn record(output_dir: &Path, page_path: &str, files: &[String]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path(output_dir))
+        .wrap_err("Failed to open checkpoint")?;
+
+    let fields: Vec<&str> = std::iter::once(page_path)
+        .chain(files.iter().map(|file| file.as_str()))
+        .collect();
+
+    writeln!(file, "{}", fields.join("\t")).wrap_err("Failed to write checkpoint")
+}
+
+/// Remove the checkpoint, e.g. after the conversion has finished.
+pub(crate) fn clear(output_dir: &Path) -> Result<()> {
+    let path = path(output_dir);
+
+    if path.is_file() {
+        fs::remove_file(path).wrap_err("Failed to remove checkpoint")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use crate::cache::collect_files;
+use crate::checkpoint;
+use crate::nav;
+use color_eyre::eyre::{Result, WrapErr};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::{Component, Path};
+
+const FILE_NAME: &str = "SHA256SUMS";
+
+/// Write a `SHA256SUMS` file with the checksums of all files in the output
+/// directory, in the format of `sha256sum`.
+pub(crate) fn write(output_dir: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    collect_files(output_dir, &mut files)?;
+    files.sort();
+
+    let mut sums = String::new();
+
+    for file in files {
+        let relative_path = file.strip_prefix(output_dir)?;
+
+        if relative_path == Path::new(FILE_NAME)
+            || relative_path == Path::new(checkpoint::FILE_NAME)
+            || relative_path == Path::new(nav::STATE_FILE_NAME)
+        {
+            continue;
+        }
+
+        let mut hasher = Sha256::new();
+        io::copy(
+            &mut File::open(&file).wrap_err("Failed to open output file")?,
+            &mut hasher,
+        )
+        .wrap_err("Failed to read output file")?;
+
+        sums.push_str(&format!(
+            "{:x}  {}\n",
+            hasher.finalize(),
+            file_path(relative_path)
+        ));
+    }
+
+    fs::write(output_dir.join(FILE_NAME), sums).wrap_err("Failed to write checksums")
+}
+
+/// The path of a file as listed in the checksums file, using `/` as separator.
+fn file_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
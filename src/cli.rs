@@ -1,14 +1,715 @@
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
 
-#[derive(StructOpt, Debug)]
+#[derive(StructOpt, Debug, Clone)]
 #[structopt(name = "one2html")]
 pub(crate) struct Opt {
-    /// Input files (`.one` or `.onetoc2` files)
+    /// Input files (`.one` or `.onetoc2` files, or `https://` URLs of `.one` files)
     #[structopt(short, long, required = true, parse(from_os_str))]
     pub(crate) input: Vec<PathBuf>,
 
+    /// HTTP header to send when downloading input files from `https://` URLs,
+    /// e.g. `Authorization: Bearer <token>`
+    #[structopt(long = "header")]
+    pub(crate) headers: Vec<String>,
+
     /// Output directory
+    #[structopt(
+        short,
+        long,
+        required_unless_one = &["list", "serve", "merge-into"],
+        parse(from_os_str)
+    )]
+    pub(crate) output: Option<PathBuf>,
+
+    /// Add the converted sections to the existing output directory of a
+    /// previous run, updating its navigation and index instead of
+    /// converting everything again
+    #[structopt(long, parse(from_os_str), conflicts_with = "output")]
+    pub(crate) merge_into: Option<PathBuf>,
+
+    /// Serve the output directory over HTTP at this address (e.g.
+    /// `127.0.0.1:8080`) after converting. Without `--output`, a temporary
+    /// directory is used
+    #[structopt(long)]
+    pub(crate) serve: Option<SocketAddr>,
+
+    /// Convert the input files again whenever they change. With `--serve`,
+    /// the pages open in the browser reload automatically
+    #[structopt(long)]
+    pub(crate) watch: bool,
+
+    /// List the sections and pages of the input files instead of converting them
+    #[structopt(long)]
+    pub(crate) list: bool,
+
+    /// Language of the generated pages (`en` or `de`)
+    #[structopt(long, default_value = "en")]
+    pub(crate) lang: String,
+
+    /// TOML file with translations that override the built-in strings
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) strings: Option<PathBuf>,
+
+    /// TOML file that maps note tags (by label or symbol name) to custom HTML
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) note_tag_map: Option<PathBuf>,
+
+    /// Only convert the page with this title (page IDs aren't supported)
+    #[structopt(long)]
+    pub(crate) page: Option<String>,
+
+    /// Split pages into multiple files at top-level headings or by size
+    #[structopt(long, possible_values = &["heading", "size"])]
+    pub(crate) split_by: Option<SplitMode>,
+
+    /// Maximum size of a page part in KiB when splitting pages by size
+    #[structopt(long, default_value = "512")]
+    pub(crate) split_size: usize,
+
+    /// Merge all pages of a section or notebook into a single HTML file
+    #[structopt(long, possible_values = &["section", "notebook"])]
+    pub(crate) merge: Option<MergeMode>,
+
+    /// Include the notebook's recycle bin as a "Deleted Pages" section group
+    #[structopt(long)]
+    pub(crate) include_deleted: bool,
+
+    /// How to handle pages with conflicting changes: list them separately,
+    /// skip them or append them to the page they conflict with
+    #[structopt(long, possible_values = &["separate", "skip", "append"])]
+    pub(crate) conflicts: Option<ConflictMode>,
+
+    /// Format of the converted pages: `html`, `confluence` (Confluence's XHTML
+    /// storage format, which can be imported with its REST API), `mediawiki`,
+    /// `tiddlywiki` (JSON files that can be imported into a wiki) or `dokuwiki`
+    /// (in `pages` and `media` folders matching DokuWiki's data directory)
+    #[structopt(
+        long,
+        default_value = "html",
+        possible_values = &["html", "confluence", "mediawiki", "tiddlywiki", "dokuwiki"]
+    )]
+    pub(crate) format: OutputFormat,
+
+    /// Only write the content of each page (and its styles) without the
+    /// surrounding `<html>`, `<head>` and `<body>` elements
+    #[structopt(long)]
+    pub(crate) fragment: bool,
+
+    /// Only write the structure and emphasis of the pages, without positions,
+    /// fonts, colors and drawings
+    #[structopt(long)]
+    pub(crate) clean: bool,
+
+    /// Use `<strong>`, `<em>`, `<mark>`, `<del>`, `<sub>` and `<sup>` for bold,
+    /// italic, highlighted, struck through, subscript and superscript text
+    /// instead of styled `<span>` elements
+    #[structopt(long)]
+    pub(crate) semantic_inline: bool,
+
+    /// Add buttons to collapse and expand indented paragraphs in the browser
+    #[structopt(long)]
+    pub(crate) outline_controls: bool,
+
+    /// Show the word count and estimated reading time below each page's title
+    #[structopt(long)]
+    pub(crate) reading_time: bool,
+
+    /// Show a thumbnail of each page's first image in the section index
+    #[structopt(long)]
+    pub(crate) thumbnails: bool,
+
+    /// Use the utility classes of a CSS framework (`bootstrap` or `tailwind`)
+    /// for bold, italic, underlined, struck through and aligned text
+    #[structopt(long, possible_values = &["bootstrap", "tailwind"])]
+    pub(crate) utility_classes: Option<UtilityClasses>,
+
+    /// Look of the generated pages: `onenote`, `minimal`, `sepia` or
+    /// `high-contrast`
+    #[structopt(
+        long,
+        default_value = "onenote",
+        possible_values = &["onenote", "minimal", "sepia", "high-contrast"]
+    )]
+    pub(crate) theme: Theme,
+
+    /// How to export drawings: as `svg` in the page, or additionally as
+    /// `inkml` files that the drawings link to
+    #[structopt(long, default_value = "svg", possible_values = &["svg", "inkml"])]
+    pub(crate) ink: InkMode,
+
+    /// Name images and attachments after the hash of their contents
+    #[structopt(long)]
+    pub(crate) hash_assets: bool,
+
+    /// URL the output directory will be published at, used for canonical links
+    /// and the sitemap
+    #[structopt(long, parse(from_str = parse_base_url))]
+    pub(crate) base_url: Option<String>,
+
+    /// URL the output directory's images and attachments will be served from,
+    /// if different from the pages
+    #[structopt(long, parse(from_str = parse_base_url))]
+    pub(crate) asset_url_prefix: Option<String>,
+
+    /// Store images and attachments once in the output directory's `.assets`
+    /// folder and link to them using hard or symbolic links
+    #[structopt(long, possible_values = &["hard", "sym"])]
+    pub(crate) link_assets: Option<LinkMode>,
+
+    /// Don't write images and attachments, render placeholders instead
+    #[structopt(long)]
+    pub(crate) no_assets: bool,
+
+    /// Don't write attachments larger than this size (e.g. `25MB`), render
+    /// placeholders instead
+    #[structopt(long, parse(try_from_str = parse_size))]
+    pub(crate) max_attachment_size: Option<usize>,
+
+    /// Only write attachments of these types, given as file extensions or MIME
+    /// types (e.g. `pdf,image/*`)
+    #[structopt(long, use_delimiter = true)]
+    pub(crate) allow_attachment_types: Vec<String>,
+
+    /// Don't write attachments of these types, given as file extensions or MIME
+    /// types (e.g. `exe,dll`)
+    #[structopt(long, use_delimiter = true)]
+    pub(crate) deny_attachment_types: Vec<String>,
+
+    /// Write the plain text of each page to a `.txt` file next to its HTML file
+    #[structopt(long)]
+    pub(crate) text: bool,
+
+    /// Embed schema.org JSON-LD metadata in each page
+    #[structopt(long)]
+    pub(crate) json_ld: bool,
+
+    /// Write a `nav.json` file describing the notebooks, sections and pages
+    /// in the output directory, for use by custom frontends
+    #[structopt(long)]
+    pub(crate) nav_json: bool,
+
+    /// Write a `stats.html` summary and a `stats.json` file with word, image,
+    /// attachment and note tag counts per section and page
+    #[structopt(long)]
+    pub(crate) stats: bool,
+
+    /// Write a CSV file listing every converted page with its section, title,
+    /// word count, attachment count and output path
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) inventory: Option<PathBuf>,
+
+    /// Ask search engines not to index the pages
+    #[structopt(long)]
+    pub(crate) noindex: bool,
+
+    /// Write a `robots.txt` file that asks crawlers not to crawl the output
+    /// directory
+    #[structopt(long)]
+    pub(crate) robots_txt: bool,
+
+    /// Place all content exactly as on OneNote's canvas: scale images to their
+    /// layout size and position attachments placed directly on the page
+    #[structopt(long)]
+    pub(crate) exact_layout: bool,
+
+    /// Add a favicon in the color of the section to every page
+    #[structopt(long)]
+    pub(crate) favicon: bool,
+
+    /// An image to use as favicon and web app icon instead of the generated
+    /// icons
+    #[structopt(long, parse(from_os_str), requires = "favicon")]
+    pub(crate) favicon_image: Option<PathBuf>,
+
+    /// Write a web app manifest and a service worker that make the output
+    /// installable as an app that can be read offline
+    #[structopt(long)]
+    pub(crate) offline: bool,
+
+    /// Write a `SHA256SUMS` file with the checksums of all pages and assets in
+    /// the output directory
+    #[structopt(long)]
+    pub(crate) checksums: bool,
+
+    /// Annotate outlines, paragraphs, tables, images and embedded files with
+    /// `data-onenote-type` and `data-onenote-index` attributes
+    #[structopt(long)]
+    pub(crate) data_attributes: bool,
+
+    /// Link each page back to its source using the `onenote:` protocol
+    #[structopt(long)]
+    pub(crate) onenote_links: bool,
+
+    /// Show the author of each page as a tooltip on its title
+    #[structopt(long)]
+    pub(crate) show_authors: bool,
+
+    /// Remove personal metadata like author names, original file names and
+    /// paths, and image EXIF data from the output
+    #[structopt(long)]
+    pub(crate) strip_metadata: bool,
+
+    /// Remove soft hyphens from the text instead of writing them as `&#173;`,
+    /// e.g. for targets that show them as visible hyphens
+    #[structopt(long)]
+    pub(crate) strip_soft_hyphens: bool,
+
+    /// A TOML file with rules for text to redact from the pages, given as
+    /// regular expressions or literal strings
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) redact: Option<PathBuf>,
+
+    /// A TOML file with find-and-replace rules for the text and links of the
+    /// pages, optionally limited to some sections
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) replace: Option<PathBuf>,
+
+    /// An HTML file that's added as a footer to every page. Supports the
+    /// placeholders `{notebook}`, `{section}`, `{page}`, `{date}` and
+    /// `{version}`
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) footer: Option<PathBuf>,
+
+    /// Make the output independent of the time of the conversion: the footer's
+    /// `{date}` is `SOURCE_DATE_EPOCH` or 1970-01-01 instead of the current time
+    #[structopt(long)]
+    pub(crate) deterministic: bool,
+
+    /// A shell command that's run on each written page, with `{path}`
+    /// replaced by the page's path. Without `{path}`, the page is passed to
+    /// the command on stdin and replaced with its output
+    #[structopt(long)]
+    pub(crate) post_process: Option<String>,
+
+    /// A rhai script with a `transform(content, page)` function that
+    /// transforms each rendered page before it's written
+    #[cfg(feature = "scripting")]
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) script: Option<PathBuf>,
+
+    /// Render each page with headless Chromium to a `png` screenshot or a
+    /// `pdf` document stored next to the page
+    #[cfg(feature = "snapshots")]
+    #[structopt(long)]
+    pub(crate) snapshot: Option<SnapshotFormat>,
+
+    /// The Chromium or Chrome executable used for `--snapshot`
+    #[cfg(feature = "snapshots")]
+    #[structopt(long, default_value = "chromium")]
+    pub(crate) chromium: String,
+
+    /// How to show attached HTML files: `inline` (sanitized and included in the
+    /// page) or `iframe` (in a sandboxed frame). Attached HTML files are only
+    /// linked by default
+    #[structopt(long, possible_values = &["inline", "iframe"])]
+    pub(crate) html_attachments: Option<HtmlAttachmentMode>,
+
+    /// Show the headers and text of attached emails (`.eml` and `.mht` files)
+    /// in the page instead of only linking them
+    #[structopt(long)]
+    pub(crate) emails: bool,
+
+    /// Write a placeholder for pages that fail to render instead of aborting
+    #[structopt(long)]
+    pub(crate) keep_going: bool,
+
+    /// Number of threads to write images and attachments with
+    #[structopt(long, default_value = "1")]
+    pub(crate) asset_threads: usize,
+
+    /// Parse and render the sections of a notebook one at a time and write
+    /// assets without queueing them to reduce memory usage
+    #[structopt(long)]
+    pub(crate) low_memory: bool,
+
+    /// Format of warnings and errors: `human` or `json` (one JSON object per line)
+    #[structopt(long, default_value = "human", possible_values = &["human", "json"])]
+    pub(crate) message_format: MessageFormat,
+
+    /// How to show the progress: `bar` or `json` (one JSON event per line on stderr)
+    #[structopt(long, default_value = "bar", possible_values = &["bar", "json"])]
+    pub(crate) progress: ProgressFormat,
+
+    /// Print the parse time of each input file and the slowest pages
+    #[structopt(long)]
+    pub(crate) timings: bool,
+
+    /// Directory to remember converted input files in. Input files that haven't
+    /// changed since they were last converted with the same options are
+    /// skipped if their output still exists
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) cache_dir: Option<PathBuf>,
+
+    /// Resume an interrupted conversion, skipping the pages it has completed
+    #[structopt(long)]
+    pub(crate) resume: bool,
+
+    /// Prefix the file names of subpages with the names of their parent pages
+    #[structopt(long)]
+    pub(crate) subpage_prefix: bool,
+
+    /// The file names of pages, without extension. Supports the placeholders
+    /// `{title}`, `{section}`, `{index}`, `{level}` and `{path}`
+    #[structopt(long)]
+    pub(crate) filename_template: Option<FilenameTemplate>,
+}
+
+/// Compare two versions of a section (`one2html diff old.one new.one`)
+#[derive(StructOpt, Debug)]
+#[structopt(name = "one2html diff")]
+pub(crate) struct DiffOpt {
+    /// The old version of the section
+    #[structopt(parse(from_os_str))]
+    pub(crate) old: PathBuf,
+
+    /// The new version of the section
+    #[structopt(parse(from_os_str))]
+    pub(crate) new: PathBuf,
+
+    /// Write a side-by-side comparison of the changed pages to this HTML file
     #[structopt(short, long, parse(from_os_str))]
-    pub(crate) output: PathBuf,
+    pub(crate) output: Option<PathBuf>,
+
+    /// Language of the comparison (`en` or `de`)
+    #[structopt(long, default_value = "en")]
+    pub(crate) lang: String,
+}
+
+/// Parse a file size like `25MB` or `512KiB` into bytes.
+fn parse_size(size: &str) -> Result<usize, String> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or_else(|| size.len());
+    let (value, unit) = size.split_at(split);
+
+    let value: f64 = value
+        .parse()
+        .map_err(|_| format!("Invalid size: {}", size))?;
+
+    let factor = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "kib" => 1024,
+        "m" | "mb" => 1000 * 1000,
+        "mib" => 1024 * 1024,
+        "g" | "gb" => 1000 * 1000 * 1000,
+        "gib" => 1024 * 1024 * 1024,
+        unit => return Err(format!("Invalid size unit: {}", unit)),
+    };
+
+    Ok((value * factor as f64) as usize)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum SplitMode {
+    Heading,
+    Size,
+}
+
+impl FromStr for SplitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "heading" => Ok(SplitMode::Heading),
+            "size" => Ok(SplitMode::Size),
+            _ => Err(format!("Invalid split mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum MergeMode {
+    Section,
+    Notebook,
+}
+
+impl FromStr for MergeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "section" => Ok(MergeMode::Section),
+            "notebook" => Ok(MergeMode::Notebook),
+            _ => Err(format!("Invalid merge mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum ConflictMode {
+    Separate,
+    Skip,
+    Append,
+}
+
+impl FromStr for ConflictMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "separate" => Ok(ConflictMode::Separate),
+            "skip" => Ok(ConflictMode::Skip),
+            "append" => Ok(ConflictMode::Append),
+            _ => Err(format!("Invalid conflict mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            _ => Err(format!("Invalid message format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum ProgressFormat {
+    Bar,
+    Json,
+}
+
+impl FromStr for ProgressFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bar" => Ok(ProgressFormat::Bar),
+            "json" => Ok(ProgressFormat::Json),
+            _ => Err(format!("Invalid progress format: {}", s)),
+        }
+    }
+}
+
+fn parse_base_url(url: &str) -> String {
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        url.to_string() + "/"
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum HtmlAttachmentMode {
+    Inline,
+    Iframe,
+}
+
+impl FromStr for HtmlAttachmentMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inline" => Ok(HtmlAttachmentMode::Inline),
+            "iframe" => Ok(HtmlAttachmentMode::Iframe),
+            _ => Err(format!("Invalid HTML attachment mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Theme {
+    OneNote,
+    Minimal,
+    Sepia,
+    HighContrast,
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "onenote" => Ok(Theme::OneNote),
+            "minimal" => Ok(Theme::Minimal),
+            "sepia" => Ok(Theme::Sepia),
+            "high-contrast" => Ok(Theme::HighContrast),
+            _ => Err(format!("Invalid theme: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum OutputFormat {
+    Html,
+    Confluence,
+    MediaWiki,
+    TiddlyWiki,
+    DokuWiki,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "html" => Ok(OutputFormat::Html),
+            "confluence" => Ok(OutputFormat::Confluence),
+            "mediawiki" => Ok(OutputFormat::MediaWiki),
+            "tiddlywiki" => Ok(OutputFormat::TiddlyWiki),
+            "dokuwiki" => Ok(OutputFormat::DokuWiki),
+            _ => Err(format!("Invalid output format: {}", s)),
+        }
+    }
+}
+
+#[cfg(feature = "snapshots")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum SnapshotFormat {
+    Png,
+    Pdf,
+}
+
+#[cfg(feature = "snapshots")]
+impl SnapshotFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Png => "png",
+            SnapshotFormat::Pdf => "pdf",
+        }
+    }
+}
+
+#[cfg(feature = "snapshots")]
+impl FromStr for SnapshotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(SnapshotFormat::Png),
+            "pdf" => Ok(SnapshotFormat::Pdf),
+            _ => Err(format!("Invalid snapshot format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum UtilityClasses {
+    Bootstrap,
+    Tailwind,
+}
+
+impl FromStr for UtilityClasses {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bootstrap" => Ok(UtilityClasses::Bootstrap),
+            "tailwind" => Ok(UtilityClasses::Tailwind),
+            _ => Err(format!("Invalid CSS framework: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum InkMode {
+    Svg,
+    InkMl,
+}
+
+impl FromStr for InkMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(InkMode::Svg),
+            "inkml" => Ok(InkMode::InkMl),
+            _ => Err(format!("Invalid ink mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum LinkMode {
+    Hard,
+    Symbolic,
+}
+
+impl FromStr for LinkMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hard" => Ok(LinkMode::Hard),
+            "sym" => Ok(LinkMode::Symbolic),
+            _ => Err(format!("Invalid link mode: {}", s)),
+        }
+    }
+}
+
+/// A template for the file names of pages, e.g. `{index} {title}`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FilenameTemplate(String);
+
+impl FilenameTemplate {
+    const PLACEHOLDERS: [&'static str; 5] = ["title", "section", "index", "level", "path"];
+
+    /// Replace the placeholders with the page's `fields`. Placeholders in the
+    /// values themselves are kept as they are.
+    pub(crate) fn render(&self, fields: &[(&str, String)]) -> String {
+        let mut name = String::new();
+        let mut rest = self.0.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            let placeholder = &rest[start + 1..end];
+
+            name.push_str(&rest[..start]);
+
+            if let Some((_, value)) = fields.iter().find(|(key, _)| *key == placeholder) {
+                name.push_str(&value.replace(&['/', '\\'][..], "_"));
+            }
+
+            rest = &rest[end + 1..];
+        }
+
+        name.push_str(rest);
+
+        name
+    }
+}
+
+impl FromStr for FilenameTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(&['/', '\\'][..]) {
+            return Err("File name templates must not contain path separators".to_string());
+        }
+
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("Unclosed placeholder in file name template: {}", s))?;
+            let placeholder = &rest[start + 1..start + end];
+
+            if !Self::PLACEHOLDERS.contains(&placeholder) {
+                return Err(format!("Unknown placeholder: {{{}}}", placeholder));
+            }
+
+            rest = &rest[start + end + 1..];
+        }
+
+        Ok(FilenameTemplate(s.to_string()))
+    }
 }
@@ -0,0 +1,80 @@
+use crate::status;
+use crate::utils::json_string;
+use color_eyre::eyre::{eyre, Report, Result};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Logger that writes each message as a JSON object on its own line to stderr.
+struct JsonLogger;
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let severity = match record.level() {
+            Level::Error => "error",
+            Level::Warn => "warning",
+            _ => "info",
+        };
+
+        // The exit code the reported problem leads to
+        let code = match record.target() {
+            status::SKIPPED => status::EXIT_PARTIAL,
+            status::NO_MATCH => status::EXIT_NO_MATCH,
+            _ if record.level() == Level::Error => status::EXIT_ERROR,
+            _ => status::EXIT_SUCCESS,
+        };
+
+        eprintln!(
+            "{{\"severity\":{},\"code\":{},\"source\":{},{}\"message\":{}}}",
+            json_string(severity),
+            code,
+            json_string(record.module_path().unwrap_or_else(|| record.target())),
+            page_field(),
+            json_string(&record.args().to_string())
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+/// Report warnings and errors as newline-delimited JSON objects.
+pub(crate) fn init_json() -> Result<()> {
+    log::set_logger(&JsonLogger).map_err(|_| eyre!("Logger is already initialized"))?;
+    log::set_max_level(LevelFilter::Info);
+
+    JSON.store(true, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// The `page` field with the title of the page that is being rendered, if any.
+fn page_field() -> String {
+    match status::current_page() {
+        Some(title) => format!("\"page\":{},", json_string(&title)),
+        None => String::new(),
+    }
+}
+
+/// Print the error that caused the conversion to fail with the exit code
+/// `code`.
+pub(crate) fn print_error(err: &Report, code: i32) {
+    if JSON.load(Ordering::Relaxed) {
+        eprintln!(
+            "{{\"severity\":\"error\",\"code\":{},{}\"message\":{}}}",
+            code,
+            page_field(),
+            json_string(&format!("{:#}", err))
+        );
+    } else {
+        eprintln!("{:?}", err);
+    }
+}
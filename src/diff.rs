@@ -0,0 +1,126 @@
+use crate::cli::DiffOpt;
+use crate::page::text::page_text;
+use crate::strings::strings;
+use crate::templates;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use console::style;
+use onenote_parser::Parser;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A page that differs between the two versions of a section.
+pub(crate) enum Change {
+    Added {
+        title: String,
+    },
+    Removed {
+        title: String,
+    },
+    Changed {
+        title: String,
+        old_text: String,
+        new_text: String,
+    },
+}
+
+/// Compare two versions of a section, print the pages that have been added,
+/// removed or changed and optionally write a side-by-side comparison.
+pub(crate) fn diff(opt: &DiffOpt) -> Result<()> {
+    let old = section_pages(&opt.old)?;
+    let new = section_pages(&opt.new)?;
+
+    let changes = compare(old, new);
+
+    if changes.is_empty() {
+        println!("No pages have changed");
+    }
+
+    for change in &changes {
+        match change {
+            Change::Added { title } => println!("{} {}", style("+").green(), title),
+            Change::Removed { title } => println!("{} {}", style("-").red(), title),
+            Change::Changed { title, .. } => println!("{} {}", style("~").yellow(), title),
+        }
+    }
+
+    if let Some(output) = &opt.output {
+        fs::write(output, templates::diff::render(&changes)?)
+            .wrap_err("Failed to write comparison")?;
+    }
+
+    Ok(())
+}
+
+/// Match the pages of both versions by their title (and the number of pages
+/// with the same title before them) and compare their text.
+fn compare(old: Vec<(String, String)>, new: Vec<(String, String)>) -> Vec<Change> {
+    let mut old_pages: HashMap<(String, usize), String> = HashMap::new();
+    let mut old_keys = Vec::new();
+
+    for (key, text) in with_occurrences(old) {
+        old_keys.push(key.clone());
+        old_pages.insert(key, text);
+    }
+
+    let mut changes = Vec::new();
+
+    for ((title, n), new_text) in with_occurrences(new) {
+        match old_pages.remove(&(title.clone(), n)) {
+            Some(old_text) if old_text != new_text => changes.push(Change::Changed {
+                title,
+                old_text,
+                new_text,
+            }),
+            Some(_) => {}
+            None => changes.push(Change::Added { title }),
+        }
+    }
+
+    for key in old_keys {
+        if old_pages.contains_key(&key) {
+            changes.push(Change::Removed { title: key.0 });
+        }
+    }
+
+    changes
+}
+
+fn with_occurrences(pages: Vec<(String, String)>) -> Vec<((String, usize), String)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    pages
+        .into_iter()
+        .map(|(title, text)| {
+            let count = counts.entry(title.clone()).or_default();
+            *count += 1;
+
+            ((title, *count), text)
+        })
+        .collect()
+}
+
+/// The titles and plain text of the pages of a section.
+fn section_pages(path: &Path) -> Result<Vec<(String, String)>> {
+    if path.extension().map_or(true, |ext| ext != "one") {
+        return Err(eyre!("Only sections (.one files) can be compared"));
+    }
+
+    let section = Parser::new()
+        .parse_section(path)
+        .wrap_err_with(|| format!("Failed to parse {}", path.display()))?;
+
+    Ok(section
+        .page_series()
+        .iter()
+        .flat_map(|series| series.pages())
+        .map(|page| {
+            let title = page
+                .title_text()
+                .unwrap_or(&strings().untitled_page)
+                .to_string();
+
+            (title, page_text(page))
+        })
+        .collect())
+}
@@ -0,0 +1,88 @@
+use color_eyre::eyre::{eyre, ContextCompat, Result, WrapErr};
+use console::style;
+use percent_encoding::percent_decode_str;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of download directories created so far, to name them uniquely.
+static DOWNLOADS: AtomicUsize = AtomicUsize::new(0);
+
+/// The URL of an input file given as `https://` or `http://` URL.
+pub(crate) fn url(input: &Path) -> Option<&str> {
+    input
+        .to_str()
+        .filter(|input| input.starts_with("https://") || input.starts_with("http://"))
+}
+
+/// Download the file at `url` into a temporary directory, sending the HTTP
+/// `headers` (given as `Name: Value`) with the request.
+pub(crate) fn download(url: &str, headers: &[String]) -> Result<PathBuf> {
+    println!("Downloading {}...", style(url).bright());
+
+    let file_name = url
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .wrap_err("URL doesn't contain a file name")?;
+    let file_name = sanitize_filename::sanitize(percent_decode_str(file_name).decode_utf8_lossy());
+
+    let mut request = ureq::get(url);
+
+    for header in headers {
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| eyre!("Invalid HTTP header: {}", header))?;
+
+        request = request.set(name.trim(), value.trim());
+    }
+
+    let response = request.call().wrap_err("Failed to download input file")?;
+
+    // Keep the file name so that the section is named like the original file
+    let dir = create_dir()?;
+    let path = dir.join(file_name);
+
+    let result = File::create(&path)
+        .wrap_err("Failed to create downloaded file")
+        .and_then(|mut file| {
+            io::copy(&mut response.into_reader(), &mut file)
+                .wrap_err("Failed to download input file")
+        });
+
+    if let Err(err) = result {
+        let _ = fs::remove_dir_all(&dir);
+
+        return Err(err);
+    }
+
+    Ok(path)
+}
+
+/// Create a new temporary directory for a download. Directories that already
+/// exist (e.g. from another run) are never reused.
+fn create_dir() -> Result<PathBuf> {
+    loop {
+        let id = DOWNLOADS.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("one2html-download-{}-{}", process::id(), id));
+
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err).wrap_err("Failed to create download directory"),
+        }
+    }
+}
+
+/// Remove a downloaded file and its temporary directory.
+pub(crate) fn remove(path: &Path) -> Result<()> {
+    let dir = path
+        .parent()
+        .wrap_err("Downloaded file has no parent folder")?;
+
+    fs::remove_dir_all(dir).wrap_err("Failed to remove downloaded file")
+}
@@ -0,0 +1,248 @@
+use crate::export::{decode_entities, file_name};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// A comment or doctype, a tag (with its name, attributes and the slash of a
+/// self-closing tag), text or a stray `<`. Unlike [`super::TOKEN`], quoted
+/// attribute values may contain `>`.
+static TOKEN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"<!--(?s:.*?)-->|<![^>]*>|<(/?)([a-zA-Z][a-zA-Z0-9]*)((?:[^>"']|"[^"]*"|'[^']*')*?)(/?)>|([^<]+)|<"#,
+    )
+    .unwrap()
+});
+
+/// An attribute with a double-quoted, single-quoted, unquoted or no value.
+static ATTRIBUTE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"([^\s"'<>/=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'<>]+)))?"#).unwrap()
+});
+
+/// Attribute names that are valid in XML.
+static ATTRIBUTE_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[a-zA-Z_:][a-zA-Z0-9_:.-]*$").unwrap());
+
+/// Elements that HTML doesn't close.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+enum Token<'a> {
+    Open {
+        name: String,
+        attributes: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    Close(String),
+    Text(&'a str),
+}
+
+impl Token<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        match self {
+            Token::Open { attributes, .. } => attributes
+                .iter()
+                .find(|(attribute, _)| attribute == name)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+
+    fn is_open(&self, tag: &str) -> bool {
+        matches!(self, Token::Open { name, .. } if name == tag)
+    }
+}
+
+/// Convert a page's clean HTML to Confluence's XHTML storage format. Images
+/// and attachments refer to page attachments of the same name, so the page's
+/// asset files have to be uploaded as attachments along with the page.
+///
+/// The HTML is parsed into tags and text and written as well-formed XML: void
+/// elements are self-closed, attributes are quoted, named entities (which XML
+/// doesn't know) are decoded and unclosed elements are closed.
+pub(crate) fn storage_format(content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut output = String::new();
+
+    // The names of the open elements and the markup that closes them
+    let mut open: Vec<(String, String)> = Vec::new();
+    // The depth of an attachment link whose contents are replaced by a macro
+    let mut skip_depth: Option<usize> = None;
+    let mut tokens = tokens.iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => {
+                if skip_depth.is_none() {
+                    output.push_str(&xml_escape(&decode_entities(text)));
+                }
+            }
+            Token::Close(name) => {
+                // Ignore closing tags without an open element
+                let index = match open.iter().rposition(|(open, _)| open == name) {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+                while open.len() > index {
+                    let (_, closing) = open.pop().unwrap();
+
+                    if skip_depth.is_none() {
+                        output.push_str(&closing);
+                    }
+
+                    if skip_depth.map_or(false, |depth| open.len() < depth) {
+                        skip_depth = None;
+                    }
+                }
+            }
+            Token::Open {
+                name, self_closing, ..
+            } if skip_depth.is_some() => {
+                if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                    open.push((name.clone(), String::new()));
+                }
+            }
+            Token::Open { name, .. } if name == "img" => {
+                if let Some(src) = token.attribute("src") {
+                    output.push_str(&format!(
+                        "<ac:image><ri:attachment ri:filename=\"{}\" /></ac:image>",
+                        xml_escape(&file_name(src))
+                    ));
+                }
+            }
+            Token::Open { name, .. }
+                if name == "a" && token.attribute("class") == Some("attachment") =>
+            {
+                if let Some(href) = token.attribute("href") {
+                    output.push_str(&format!(
+                        "<ac:structured-macro ac:name=\"view-file\"><ac:parameter ac:name=\"name\">\
+                         <ri:attachment ri:filename=\"{}\" /></ac:parameter></ac:structured-macro>",
+                        xml_escape(&file_name(href))
+                    ));
+                }
+
+                open.push((name.clone(), String::new()));
+                skip_depth = Some(open.len());
+            }
+            // A task is a `div` that starts with a checkbox
+            Token::Open { name, .. }
+                if name == "div"
+                    && tokens.peek().map_or(false, |next| {
+                        next.is_open("input") && next.attribute("type") == Some("checkbox")
+                    }) =>
+            {
+                let checkbox = tokens.next().unwrap();
+                let status = if checkbox.attribute("checked").is_some() {
+                    "complete"
+                } else {
+                    "incomplete"
+                };
+
+                output.push_str(&format!(
+                    "<ac:task-list><ac:task><ac:task-status>{}</ac:task-status><ac:task-body>",
+                    status
+                ));
+                open.push((
+                    name.clone(),
+                    "</ac:task-body></ac:task></ac:task-list>".to_string(),
+                ));
+            }
+            Token::Open {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                output.push('<');
+                output.push_str(name);
+
+                for (attribute, value) in attributes {
+                    output.push_str(&format!(" {}=\"{}\"", attribute, xml_escape(value)));
+                }
+
+                if *self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                    output.push_str(" />");
+                } else {
+                    output.push('>');
+                    open.push((name.clone(), format!("</{}>", name)));
+                }
+            }
+        }
+    }
+
+    while let Some((_, closing)) = open.pop() {
+        output.push_str(&closing);
+    }
+
+    output
+}
+
+/// Split the HTML `content` into tags and text, skipping comments and
+/// doctypes.
+fn tokenize(content: &str) -> Vec<Token> {
+    TOKEN
+        .captures_iter(content)
+        .filter_map(|captures| {
+            if let Some(text) = captures.get(5) {
+                return Some(Token::Text(text.as_str()));
+            }
+
+            // A stray `<` is text
+            if &captures[0] == "<" {
+                return Some(Token::Text("<"));
+            }
+
+            let name = captures.get(2)?.as_str().to_ascii_lowercase();
+
+            if !captures[1].is_empty() {
+                return Some(Token::Close(name));
+            }
+
+            let mut attributes: Vec<(String, String)> = Vec::new();
+
+            for attribute in ATTRIBUTE.captures_iter(&captures[3]) {
+                let key = attribute[1].to_ascii_lowercase();
+
+                // XML doesn't allow invalid names or the same attribute twice
+                if !ATTRIBUTE_NAME.is_match(&key) || attributes.iter().any(|(name, _)| *name == key)
+                {
+                    continue;
+                }
+
+                // Boolean attributes need a value in XML
+                let value = (2..=4).find_map(|i| attribute.get(i)).map_or_else(
+                    || key.clone(),
+                    |value| decode_entities(value.as_str()).to_string(),
+                );
+
+                attributes.push((key, value));
+            }
+
+            Some(Token::Open {
+                name,
+                attributes,
+                self_closing: !captures[4].is_empty(),
+            })
+        })
+        .collect()
+}
+
+/// Escape the characters that XML text and attribute values can't contain.
+/// Invisible spaces and hyphens are written as character references.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\u{a0}' => escaped.push_str("&#160;"),
+            '\u{ad}' => escaped.push_str("&#173;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,170 @@
+use crate::cli::Opt;
+use crate::export::{
+    attribute, decode_entities, file_name, relative_path, PageInfo, BLANK_LINES, TOKEN,
+};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Text that DokuWiki would read as formatting, links, tables or lists.
+static MARKUP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\*\*|//|__|''|\[\[|\]\]|\{\{|\}\}|\(\(|\)\)|<|>|%%|\^|\||\\\\|==|--|~~|^\s*[*-]")
+        .unwrap()
+});
+
+/// Convert a page's clean HTML to DokuWiki syntax. Images and attachments
+/// refer to media files in the page's namespace.
+pub(crate) fn wikitext(page: &PageInfo, content: &str) -> String {
+    let mut output = String::new();
+    let mut lists: Vec<char> = Vec::new();
+    let mut in_attachment = false;
+    // Table cells can't contain line breaks other than `\\`
+    let mut in_cell = false;
+
+    let media = |url: &str| format!("{{{{{}:{}}}}}", page.namespace, file_name(url));
+
+    for token in TOKEN.captures_iter(content) {
+        if let Some(text) = token.get(4) {
+            if !in_attachment {
+                output.push_str(&escape(&text.as_str().replace('\n', " ")));
+            }
+
+            continue;
+        }
+
+        let closing = !token[1].is_empty();
+        let tag = token[2].to_ascii_lowercase();
+        let attr = |name: &str| attribute(&token[3], name);
+
+        match (tag.as_str(), closing) {
+            ("h1", _) | ("h2", _) | ("h3", _) | ("h4", _) | ("h5", _) | ("h6", _) => {
+                let level: usize = tag[1..].parse().unwrap_or(1);
+                let marker = "=".repeat(7 - level.min(5));
+
+                if closing {
+                    output.push_str(&format!(" {}\n", marker));
+                } else {
+                    output.push_str(&format!("\n{} ", marker));
+                }
+            }
+            ("p", true) | ("div", true) if in_cell => output.push_str("\\\\ "),
+            ("p", true) | ("div", true) if lists.is_empty() => output.push_str("\n\n"),
+            ("ul", false) => lists.push('*'),
+            ("ol", false) => lists.push('-'),
+            ("ul", true) | ("ol", true) => {
+                lists.pop();
+
+                if lists.is_empty() && !in_cell {
+                    output.push_str("\n\n");
+                }
+            }
+            ("li", false) if in_cell => {
+                let marker = lists.last().copied().unwrap_or('*');
+                output.push_str(&format!("\\\\ {} ", marker));
+            }
+            ("li", false) => {
+                let marker = lists.last().copied().unwrap_or('*');
+                output.push_str(&format!("\n{}{} ", "  ".repeat(lists.len()), marker));
+            }
+            ("strong", _) | ("b", _) => output.push_str("**"),
+            ("em", _) | ("i", _) => output.push_str("//"),
+            ("u", _) => output.push_str("__"),
+            ("s", false) => output.push_str("<del>"),
+            ("s", true) => output.push_str("</del>"),
+            ("sup", _) | ("sub", _) => output.push_str(&token[0]),
+            ("br", _) => output.push_str("\\\\ "),
+            ("hr", _) => output.push_str("\n----\n"),
+            ("table", _) => output.push_str("\n"),
+            ("tr", false) => output.push_str("\n|"),
+            ("td", true) | ("th", true) => {
+                while output.ends_with("\\\\ ") {
+                    output.truncate(output.len() - 3);
+                }
+
+                output.push_str(" |");
+                in_cell = false;
+            }
+            ("td", false) | ("th", false) => {
+                output.push(' ');
+                in_cell = true;
+            }
+            ("img", _) => {
+                if let Some(src) = attr("src") {
+                    output.push_str(&media(&src));
+                }
+            }
+            ("a", false) if attr("class").as_deref() == Some("attachment") => {
+                if let Some(href) = attr("href") {
+                    output.push_str(&media(&href));
+                }
+
+                in_attachment = true;
+            }
+            ("a", false) => match attr("href") {
+                Some(href) => output.push_str(&format!("[[{}|", href)),
+                None => output.push_str("[["),
+            },
+            ("a", true) if in_attachment => in_attachment = false,
+            ("a", true) => output.push_str("]]"),
+            ("input", _) => {
+                let checked = token[3].contains("checked");
+                output.push_str(if checked { "☑ " } else { "☐ " });
+            }
+            _ => {}
+        }
+    }
+
+    BLANK_LINES.replace_all(output.trim(), "\n\n").to_string() + "\n"
+}
+
+/// Decode the entities in the HTML `text` and keep DokuWiki from reading it as
+/// markup by wrapping it in `%%`. `%%` itself is written with `<nowiki>`.
+fn escape(text: &str) -> String {
+    let text = decode_entities(text);
+
+    if !MARKUP.is_match(&text) {
+        return text.to_string();
+    }
+
+    text.split("%%")
+        .map(|part| {
+            if part.is_empty() {
+                String::new()
+            } else {
+                format!("%%{}%%", part)
+            }
+        })
+        .join("<nowiki>%%</nowiki>")
+}
+
+/// Clean a page, media or namespace name the way DokuWiki does: lowercase,
+/// with runs of other characters than letters, digits, `-` and `.` replaced
+/// by `_`.
+pub(crate) fn clean_id(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '.'))
+        .filter(|part| !part.is_empty())
+        .join("_")
+}
+
+/// The namespace of the section in `section_dir`, derived from its path
+/// relative to the output directory, e.g. `notebook:section_group:section`.
+pub(crate) fn namespace(opt: &Opt, section_dir: &Path) -> String {
+    relative_path(opt, section_dir)
+        .iter()
+        .map(|component| clean_id(&component.to_string_lossy()))
+        .join(":")
+}
+
+/// The directory of the section in `section_dir` in DokuWiki's data
+/// directory `data_dir` (`pages` or `media`) inside the output directory.
+pub(crate) fn data_dir(opt: &Opt, section_dir: &Path, data_dir: &str) -> PathBuf {
+    let root = opt.output.clone().unwrap_or_default();
+
+    relative_path(opt, section_dir)
+        .iter()
+        .fold(root.join(data_dir), |dir, component| {
+            dir.join(clean_id(&component.to_string_lossy()))
+        })
+}
@@ -0,0 +1,101 @@
+use crate::export::{attribute, decode_entities, file_name, BLANK_LINES, TOKEN};
+use crate::templates::html_escape;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Text that MediaWiki would read as formatting, links, templates, tables or
+/// lists.
+static MARKUP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"''|\[|\]|\{\{|\}\}|\||~~~|__|----|^\s*[*#:;=]|=\s*$").unwrap());
+
+/// Convert a page's clean HTML to MediaWiki wikitext. Images and attachments
+/// refer to uploaded files of the same name.
+pub(crate) fn wikitext(content: &str) -> String {
+    let mut output = String::new();
+    let mut lists = String::new();
+    let mut in_attachment = false;
+
+    for token in TOKEN.captures_iter(content) {
+        if let Some(text) = token.get(4) {
+            if !in_attachment {
+                output.push_str(&escape(&text.as_str().replace('\n', " ")));
+            }
+
+            continue;
+        }
+
+        let closing = !token[1].is_empty();
+        let tag = token[2].to_ascii_lowercase();
+        let attr = |name: &str| attribute(&token[3], name);
+
+        match (tag.as_str(), closing) {
+            ("h1", _) | ("h2", _) | ("h3", _) | ("h4", _) | ("h5", _) | ("h6", _) => {
+                let marker = "=".repeat(tag[1..].parse().unwrap_or(1));
+
+                if closing {
+                    output.push_str(&format!(" {}\n", marker));
+                } else {
+                    output.push_str(&format!("\n{} ", marker));
+                }
+            }
+            ("p", true) | ("div", true) if lists.is_empty() => output.push('\n'),
+            ("ul", false) => lists.push('*'),
+            ("ol", false) => lists.push('#'),
+            ("ul", true) | ("ol", true) => {
+                lists.pop();
+
+                if lists.is_empty() {
+                    output.push('\n');
+                }
+            }
+            ("li", false) => output.push_str(&format!("\n{} ", lists)),
+            ("strong", _) | ("b", _) => output.push_str("'''"),
+            ("em", _) | ("i", _) => output.push_str("''"),
+            ("u", _) | ("s", _) | ("sup", _) | ("sub", _) => output.push_str(&token[0]),
+            ("br", _) => output.push_str("<br />"),
+            ("hr", _) => output.push_str("\n----\n"),
+            ("table", false) => output.push_str("\n{| class=\"wikitable\""),
+            ("table", true) => output.push_str("\n|}\n"),
+            ("tr", false) => output.push_str("\n|-"),
+            ("td", false) => output.push_str("\n| "),
+            ("img", _) => {
+                if let Some(src) = attr("src") {
+                    output.push_str(&format!("[[File:{}]]", file_name(&src)));
+                }
+            }
+            ("a", false) if attr("class").as_deref() == Some("attachment") => {
+                if let Some(href) = attr("href") {
+                    output.push_str(&format!("[[Media:{}]]", file_name(&href)));
+                }
+
+                in_attachment = true;
+            }
+            ("a", false) => match attr("href") {
+                Some(href) => output.push_str(&format!("[{} ", href)),
+                None => output.push('['),
+            },
+            ("a", true) if in_attachment => in_attachment = false,
+            ("a", true) => output.push(']'),
+            ("input", _) => {
+                let checked = token[3].contains("checked");
+                output.push_str(if checked { "☑ " } else { "☐ " });
+            }
+            _ => {}
+        }
+    }
+
+    BLANK_LINES.replace_all(output.trim(), "\n\n").to_string() + "\n"
+}
+
+/// Decode the entities in the HTML `text` and keep MediaWiki from reading it
+/// as markup. `&`, `<` and `>` are written as entities (which MediaWiki
+/// decodes), and text with wiki markup is wrapped in `<nowiki>`.
+fn escape(text: &str) -> String {
+    let text = html_escape(&decode_entities(text)).unwrap_or_default();
+
+    if MARKUP.is_match(&text) {
+        format!("<nowiki>{}</nowiki>", text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,165 @@
+use crate::cli::{Opt, OutputFormat};
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+pub(crate) mod confluence;
+pub(crate) mod dokuwiki;
+pub(crate) mod mediawiki;
+pub(crate) mod tiddlywiki;
+
+/// Information about a page that some formats include in their output.
+pub(crate) struct PageInfo<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) section: &'a str,
+    pub(crate) tags: Vec<String>,
+    /// The DokuWiki namespace of the page, e.g. `notebook:section`.
+    pub(crate) namespace: String,
+    /// The path of the page's section, e.g. `Notebook/Section`.
+    pub(crate) path: String,
+}
+
+/// An HTML tag (with its name and attributes) or text.
+pub(crate) static TOKEN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<(/?)([a-zA-Z0-9]+)([^>]*)>|([^<]+)"#).unwrap());
+
+static ATTRIBUTE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"([a-z-]+)="([^"]*)""#).unwrap());
+
+pub(crate) static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n{3,}").unwrap());
+
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z][a-zA-Z0-9]*);").unwrap());
+
+/// The named character references that can occur in a page's HTML.
+const ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("shy", '\u{ad}'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("hellip", '…'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("bull", '•'),
+    ("middot", '·'),
+    ("deg", '°'),
+    ("times", '×'),
+    ("euro", '€'),
+];
+
+impl OutputFormat {
+    /// The file extension of the converted pages.
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Html => "html",
+            OutputFormat::Confluence => "xml",
+            OutputFormat::MediaWiki => "wiki",
+            OutputFormat::TiddlyWiki => "json",
+            OutputFormat::DokuWiki => "txt",
+        }
+    }
+}
+
+/// The name of a page's file.
+pub(crate) fn page_file_name(format: OutputFormat, name: &str) -> String {
+    match format {
+        OutputFormat::DokuWiki => format!("{}.txt", dokuwiki::clean_id(name)),
+        format => sanitize_filename::sanitize(format!("{}.{}", name, format.extension())),
+    }
+}
+
+/// The path of the section in `section_dir` relative to the output
+/// directory, e.g. `Notebook/Section`.
+pub(crate) fn section_path(opt: &Opt, section_dir: &Path) -> String {
+    relative_path(opt, section_dir)
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The path of `section_dir` relative to the output directory.
+pub(crate) fn relative_path(opt: &Opt, section_dir: &Path) -> PathBuf {
+    opt.output
+        .as_ref()
+        .and_then(|root| section_dir.strip_prefix(root).ok())
+        .unwrap_or(section_dir)
+        .to_path_buf()
+}
+
+/// The directory to write the pages of the section in `section_dir` to.
+pub(crate) fn page_dir(opt: &Opt, section_dir: &Path) -> PathBuf {
+    match opt.format {
+        OutputFormat::DokuWiki => dokuwiki::data_dir(opt, section_dir, "pages"),
+        _ => section_dir.to_path_buf(),
+    }
+}
+
+/// The directory to write the images and attachments of the section in
+/// `section_dir` to.
+pub(crate) fn asset_dir(opt: &Opt, section_dir: &Path) -> PathBuf {
+    match opt.format {
+        OutputFormat::DokuWiki => dokuwiki::data_dir(opt, section_dir, "media"),
+        _ => section_dir.to_path_buf(),
+    }
+}
+
+/// Convert the clean HTML `content` of a page to a format other than HTML.
+pub(crate) fn render(format: OutputFormat, page: &PageInfo, content: &str) -> String {
+    match format {
+        OutputFormat::Html => content.to_string(),
+        OutputFormat::Confluence => confluence::storage_format(content),
+        OutputFormat::MediaWiki => mediawiki::wikitext(content),
+        OutputFormat::TiddlyWiki => tiddlywiki::tiddlers(page, content),
+        OutputFormat::DokuWiki => dokuwiki::wikitext(page, content),
+    }
+}
+
+/// Get the (decoded) value of the attribute `name` from an HTML tag's
+/// attributes.
+pub(crate) fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(attributes)
+        .find(|captures| &captures[1] == name)
+        .map(|captures| decode_entities(&captures[2]).to_string())
+}
+
+/// Replace the character references in the HTML `text` by the characters
+/// they stand for. Unknown references are kept as they are.
+pub(crate) fn decode_entities(text: &str) -> Cow<str> {
+    ENTITY.replace_all(text, |captures: &Captures| {
+        let name = &captures[1];
+
+        let decoded = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X"))
+        {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(decimal) = name.strip_prefix('#') {
+            decimal.parse().ok().and_then(char::from_u32)
+        } else {
+            ENTITIES
+                .iter()
+                .find(|(entity, _)| *entity == name)
+                .map(|(_, char)| *char)
+        };
+
+        decoded.map_or_else(|| captures[0].to_string(), |char| char.to_string())
+    })
+}
+
+/// The name of an asset file referred to by `url`.
+pub(crate) fn file_name(url: &str) -> String {
+    let name = url.rsplit('/').next().unwrap_or(url);
+
+    percent_decode_str(name).decode_utf8_lossy().to_string()
+}
@@ -0,0 +1,58 @@
+use crate::export::{decode_entities, PageInfo};
+use crate::utils::json_string;
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+
+static IMAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<img [^>]*?src="([^"]*)"[^>]*>"#).unwrap());
+
+/// Convert a page to a TiddlyWiki JSON file that can be imported into a wiki.
+/// The page becomes an HTML tiddler tagged with its section and note tags,
+/// its images become image tiddlers that refer to the image files with
+/// `_canonical_uri`. Tiddlers are titled with the section's path, as titles
+/// are unique within the wiki.
+pub(crate) fn tiddlers(page: &PageInfo, content: &str) -> String {
+    let images = IMAGE.captures_iter(content).map(|captures| {
+        let url = decode_entities(&captures[1]).to_string();
+        let name = url.rsplit('/').next().unwrap_or(&url);
+        let name = percent_decode_str(name).decode_utf8_lossy().to_string();
+
+        (name, url)
+    });
+
+    let tags = std::iter::once(page.section)
+        .chain(page.tags.iter().map(|tag| tag.as_str()))
+        .map(|tag| format!("[[{}]]", tag))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut tiddlers = vec![format!(
+        "{{\"title\":{},\"tags\":{},\"type\":\"text/html\",\"text\":{}}}",
+        json_string(&title(page, page.title)),
+        json_string(&tags),
+        json_string(content)
+    )];
+
+    for (name, url) in images.unique() {
+        let mime = mime_guess::from_path(&name).first_or_octet_stream();
+
+        tiddlers.push(format!(
+            "{{\"title\":{},\"type\":{},\"_canonical_uri\":{}}}",
+            json_string(&title(page, &name)),
+            json_string(mime.essence_str()),
+            json_string(&url)
+        ));
+    }
+
+    format!("[\n{}\n]\n", tiddlers.join(",\n"))
+}
+
+/// The title of the tiddler `name` of the page's section.
+fn title(page: &PageInfo, name: &str) -> String {
+    if page.path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", page.path, name)
+    }
+}
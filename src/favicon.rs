@@ -0,0 +1,67 @@
+use crate::notebook::RgbColor;
+use color_eyre::eyre::{Result, WrapErr};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+use once_cell::sync::OnceCell;
+use std::path::Path;
+
+static IMAGE: OnceCell<DynamicImage> = OnceCell::new();
+
+/// The color of icons for notebooks and sections without a color, OneNote's
+/// purple.
+pub(crate) const DEFAULT_COLOR: [u8; 3] = [0x77, 0x19, 0xaa];
+
+pub(crate) const FAVICON_FILE: &str = "favicon.png";
+pub(crate) const TOUCH_ICON_FILE: &str = "apple-touch-icon.png";
+
+/// Load the image at `path` to use for all icons instead of the generated
+/// ones. The image can only be set once.
+pub(crate) fn init(path: &Path) -> Result<()> {
+    let image = image::open(path).wrap_err("Failed to read icon image")?;
+
+    let _ = IMAGE.set(image);
+
+    Ok(())
+}
+
+/// Write a favicon and a touch icon in `color` to `dir`.
+pub(crate) fn write(dir: &Path, color: Option<&RgbColor>) -> Result<()> {
+    let color = color.map_or(DEFAULT_COLOR, |color| {
+        [color.color.red, color.color.green, color.color.blue]
+    });
+
+    write_icon(&dir.join(FAVICON_FILE), 32, color)?;
+    write_icon(&dir.join(TOUCH_ICON_FILE), 180, color)
+}
+
+/// Write a square icon of `size` pixels to `path`: the image given with
+/// `--favicon-image`, or a rounded square in `color`.
+pub(crate) fn write_icon(path: &Path, size: u32, color: [u8; 3]) -> Result<()> {
+    let icon = match IMAGE.get() {
+        Some(image) => image
+            .resize_to_fill(size, size, FilterType::Lanczos3)
+            .to_rgba8(),
+        None => rounded_square(size, color),
+    };
+
+    icon.save(path).wrap_err("Failed to write icon")
+}
+
+fn rounded_square(size: u32, color: [u8; 3]) -> RgbaImage {
+    let radius = size as f32 / 5.0;
+    let [red, green, blue] = color;
+
+    ImageBuffer::from_fn(size, size, |x, y| {
+        // The distance of the pixel's center from the nearest corner circle
+        let distance = |position: u32| {
+            let position = position as f32 + 0.5;
+            (radius - position)
+                .max(position - (size as f32 - radius))
+                .max(0.0)
+        };
+        let outside = (distance(x).powi(2) + distance(y).powi(2)).sqrt() - radius;
+        let alpha = (0.5 - outside).max(0.0).min(1.0);
+
+        Rgba([red, green, blue, (alpha * 255.0).round() as u8])
+    })
+}
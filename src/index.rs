@@ -0,0 +1,35 @@
+use crate::templates;
+use color_eyre::eyre::{Result, WrapErr};
+use std::fs;
+use std::path::Path;
+
+const FILE_NAME: &str = "index.html";
+
+/// Pages at the top of the output directory that aren't notebooks or sections.
+const OTHER_PAGES: [&str; 2] = [FILE_NAME, "stats.html"];
+
+/// Write an `index.html` file that links all notebooks and sections in the
+/// output directory, i.e. the HTML files at its top level.
+pub(crate) fn write(output_dir: &Path) -> Result<()> {
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(output_dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        if !path.is_file()
+            || path.extension().map_or(true, |ext| ext != "html")
+            || OTHER_PAGES.contains(&file_name.as_ref())
+        {
+            continue;
+        }
+
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        entries.push((name.to_string(), file_name.to_string()));
+    }
+
+    entries.sort();
+
+    let html = templates::index::render(&entries)?;
+    fs::write(output_dir.join(FILE_NAME), html).wrap_err("Failed to write index page")
+}
@@ -0,0 +1,60 @@
+use color_eyre::eyre::{Result, WrapErr};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+static ROWS: Lazy<Mutex<Vec<InventoryRow>>> = Lazy::new(Default::default);
+
+/// A converted page in the inventory.
+pub(crate) struct InventoryRow {
+    pub(crate) section: String,
+    pub(crate) title: String,
+    pub(crate) words: usize,
+    pub(crate) attachments: usize,
+    pub(crate) path: String,
+}
+
+/// Remember a converted page for the inventory.
+pub(crate) fn record_page(row: InventoryRow) {
+    let mut rows = ROWS.lock().expect("inventory lock poisoned");
+
+    rows.push(row);
+}
+
+/// Forget the recorded pages, e.g. before converting the inputs again.
+pub(crate) fn clear() {
+    ROWS.lock().expect("inventory lock poisoned").clear();
+}
+
+/// Write a CSV file listing all converted pages.
+pub(crate) fn write(path: &Path) -> Result<()> {
+    let rows = ROWS.lock().expect("inventory lock poisoned");
+
+    let mut csv = String::from("section,title,word_count,attachment_count,output_path\r\n");
+
+    for row in rows.iter() {
+        let fields = [
+            row.section.clone(),
+            row.title.clone(),
+            row.words.to_string(),
+            row.attachments.to_string(),
+            row.path.clone(),
+        ];
+
+        csv.push_str(&fields.iter().map(|field| csv_field(field)).join(","));
+        csv.push_str("\r\n");
+    }
+
+    fs::write(path, csv).wrap_err("Failed to write inventory")
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\r', '\n'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
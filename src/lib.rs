@@ -0,0 +1,132 @@
+//! Interfaces for embedding one2html in other applications.
+//!
+//! The library is only built with the `capi` feature (see [`capi`]) or the
+//! `python` feature, which builds a Python extension module.
+
+#![cfg(any(feature = "capi", feature = "python"))]
+
+use crate::cli::Opt;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use once_cell::sync::Lazy;
+use onenote_parser::Parser;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use structopt::StructOpt;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "python")]
+mod python;
+
+// These modules also contain the parts of the command line tool (notebooks,
+// reports, diffs) that the library doesn't use
+#[allow(dead_code)]
+mod checkpoint;
+#[allow(dead_code)]
+mod cli;
+#[allow(dead_code)]
+mod diff;
+mod export;
+#[allow(dead_code)]
+mod favicon;
+#[allow(dead_code)]
+mod inventory;
+#[allow(dead_code)]
+mod nav;
+#[allow(dead_code)]
+mod notebook;
+mod page;
+#[allow(dead_code)]
+mod progress;
+mod section;
+#[allow(dead_code)]
+mod stats;
+#[allow(dead_code)]
+mod status;
+mod strings;
+#[allow(dead_code)]
+mod templates;
+#[allow(dead_code)]
+mod timings;
+#[allow(dead_code)]
+mod utils;
+
+/// The options of the first conversion. The renderer settings (strings,
+/// theme, redaction rules, ...) are global, so they can't change afterwards.
+static OPTIONS: Lazy<Mutex<Option<Vec<String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Convert the section file `input` to HTML in `output_dir`. The conversion
+/// is configured by the command line `options`, which must be the same for
+/// all conversions in a process.
+fn convert_section(input: &Path, output_dir: &Path, options: Vec<String>) -> Result<()> {
+    let args = vec![
+        "one2html".to_string(),
+        "--input".to_string(),
+        input.to_string_lossy().to_string(),
+        "--output".to_string(),
+        output_dir.to_string_lossy().to_string(),
+    ];
+
+    let opt = Opt::from_iter_safe(args.into_iter().chain(options.iter().cloned()))
+        .wrap_err("Invalid options")?;
+
+    let mut first = OPTIONS.lock().unwrap();
+
+    match &*first {
+        Some(first) if *first != options => {
+            return Err(eyre!(
+                "The options can't change between conversions (first used: `{}`)",
+                first.join(" ")
+            ))
+        }
+        Some(_) => {}
+        None => {
+            init(&opt)?;
+            *first = Some(options);
+        }
+    }
+
+    drop(first);
+
+    let section = Parser::new().parse_section(input)?;
+
+    fs::create_dir_all(output_dir).wrap_err("Failed to create output directory")?;
+    section::Renderer::new(opt).render(&section, output_dir)?;
+
+    Ok(())
+}
+
+/// Initialize the global renderer settings from `opt`.
+fn init(opt: &Opt) -> Result<()> {
+    strings::init(&opt.lang, opt.strings.as_deref())?;
+    templates::theme::init(opt.theme);
+    templates::robots::init(opt.noindex);
+
+    if let Some(path) = &opt.note_tag_map {
+        page::note_tag_map::init(path)?;
+    }
+
+    if let Some(path) = &opt.redact {
+        page::redaction::init(path)?;
+    }
+
+    if let Some(path) = &opt.replace {
+        page::replacement::init(path)?;
+    }
+
+    if let Some(path) = &opt.favicon_image {
+        favicon::init(path)?;
+    }
+
+    if let Some(path) = &opt.footer {
+        page::footer::init(path, opt.deterministic)?;
+    }
+
+    #[cfg(feature = "scripting")]
+    if let Some(path) = &opt.script {
+        page::script::init(path)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use crate::strings::strings;
+use crate::utils::format_size;
+use color_eyre::eyre::{eyre, ContextCompat, Result};
+use console::style;
+use onenote_parser::contents::{Content, Outline, OutlineElement, OutlineItem};
+use onenote_parser::page::{Page, PageContent};
+use onenote_parser::section::{Section, SectionEntry};
+use onenote_parser::Parser;
+use std::path::Path;
+
+/// Print the section groups, sections and pages of the input file without
+/// converting it.
+pub(crate) fn list(path: &Path) -> Result<()> {
+    let mut parser = Parser::new();
+
+    match path.extension().map(|p| p.to_string_lossy()).as_deref() {
+        Some("one") => {
+            let section = parser.parse_section(&path)?;
+
+            list_section(&section, 0);
+        }
+        Some("onetoc2") => {
+            let notebook = parser.parse_notebook(&path)?;
+
+            let name = path
+                .parent()
+                .wrap_err("Input file has no parent folder")?
+                .file_name()
+                .wrap_err("Parent folder has no name")?
+                .to_string_lossy();
+
+            println!(
+                "{} ({} sections, {} pages)",
+                style(&name).bright(),
+                count_sections(notebook.entries()),
+                count_pages(notebook.entries())
+            );
+
+            list_entries(notebook.entries(), 1);
+        }
+        Some(ext) => return Err(eyre!("Invalid file extension: {}", ext)),
+        _ => return Err(eyre!("Couldn't determine file type")),
+    }
+
+    Ok(())
+}
+
+fn list_entries(entries: &[SectionEntry], depth: usize) {
+    for entry in entries {
+        match entry {
+            SectionEntry::Section(section) => list_section(section, depth),
+            SectionEntry::SectionGroup(group) => {
+                println!(
+                    "{}{}/ ({} sections, {} pages)",
+                    indent(depth),
+                    style(group.display_name()).bright(),
+                    count_sections(group.entries()),
+                    count_pages(group.entries())
+                );
+
+                list_entries(group.entries(), depth + 1);
+            }
+        }
+    }
+}
+
+fn list_section(section: &Section, depth: usize) {
+    let pages = section
+        .page_series()
+        .iter()
+        .flat_map(|series| series.pages())
+        .collect::<Vec<_>>();
+
+    let (files, size) = pages
+        .iter()
+        .map(|page| page_files(page))
+        .fold((0, 0), |(files, size), (f, s)| (files + f, size + s));
+
+    println!(
+        "{}{} ({} pages, {} files, {})",
+        indent(depth),
+        style(section.display_name()).bright(),
+        pages.len(),
+        files,
+        format_size(size)
+    );
+
+    for page in pages {
+        let (files, size) = page_files(page);
+        let level = page.level().max(1) as usize - 1;
+
+        println!(
+            "{}{} ({} files, {})",
+            indent(depth + 1 + level),
+            page.title_text().unwrap_or(&strings().untitled_page),
+            files,
+            format_size(size)
+        );
+    }
+}
+
+fn count_sections(entries: &[SectionEntry]) -> usize {
+    entries
+        .iter()
+        .map(|entry| match entry {
+            SectionEntry::Section(_) => 1,
+            SectionEntry::SectionGroup(group) => count_sections(group.entries()),
+        })
+        .sum()
+}
+
+fn count_pages(entries: &[SectionEntry]) -> usize {
+    entries
+        .iter()
+        .map(|entry| match entry {
+            SectionEntry::Section(section) => section
+                .page_series()
+                .iter()
+                .map(|series| series.pages().len())
+                .sum(),
+            SectionEntry::SectionGroup(group) => count_pages(group.entries()),
+        })
+        .sum()
+}
+
+/// Count the images and embedded files of a page along with their total size.
+fn page_files(page: &Page) -> (usize, usize) {
+    page.contents()
+        .iter()
+        .map(|content| match content {
+            PageContent::Outline(outline) => outline_files(outline),
+            PageContent::Image(image) => (1, image.data().map(|d| d.len()).unwrap_or(0)),
+            PageContent::EmbeddedFile(file) => (1, file.data().len()),
+            PageContent::Ink(_) | PageContent::Unknown => (0, 0),
+        })
+        .fold((0, 0), |(files, size), (f, s)| (files + f, size + s))
+}
+
+fn outline_files(outline: &Outline) -> (usize, usize) {
+    outline_items_files(outline.items())
+}
+
+fn outline_items_files(items: &[OutlineItem]) -> (usize, usize) {
+    items
+        .iter()
+        .map(|item| match item {
+            OutlineItem::Element(element) => outline_element_files(element),
+            OutlineItem::Group(group) => outline_items_files(group.outlines()),
+        })
+        .fold((0, 0), |(files, size), (f, s)| (files + f, size + s))
+}
+
+fn outline_element_files(element: &OutlineElement) -> (usize, usize) {
+    let (files, size) = element
+        .contents()
+        .iter()
+        .map(|content| match content {
+            Content::Image(image) => (1, image.data().map(|d| d.len()).unwrap_or(0)),
+            Content::EmbeddedFile(file) => (1, file.data().len()),
+            Content::Table(table) => table
+                .contents()
+                .iter()
+                .flat_map(|row| row.contents())
+                .flat_map(|cell| cell.contents())
+                .map(outline_element_files)
+                .fold((0, 0), |(files, size), (f, s)| (files + f, size + s)),
+            Content::RichText(_) | Content::Ink(_) | Content::Unknown => (0, 0),
+        })
+        .fold((0, 0), |(files, size), (f, s)| (files + f, size + s));
+
+    let (child_files, child_size) = outline_items_files(element.children());
+
+    (files + child_files, size + child_size)
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
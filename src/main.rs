@@ -1,68 +1,379 @@
 #![cfg_attr(feature = "backtrace", feature(backtrace))]
 
-use crate::cli::Opt;
+use crate::cli::{DiffOpt, MergeMode, MessageFormat, Opt, OutputFormat, ProgressFormat};
 use crate::utils::with_progress;
-use color_eyre::eyre::Result;
-use color_eyre::eyre::{eyre, ContextCompat};
+use color_eyre::eyre::{eyre, ContextCompat, WrapErr};
+use color_eyre::eyre::{Report, Result};
 use console::style;
-use log::LevelFilter;
+use log::{warn, LevelFilter};
 use onenote_parser::Parser;
-use std::path::Path;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::time::Instant;
 use structopt::StructOpt;
 
+mod cache;
+mod checkpoint;
+mod checksums;
 mod cli;
+mod diagnostics;
+mod diff;
+mod download;
+mod export;
+mod favicon;
+mod index;
+mod inventory;
+mod list;
+mod nav;
 mod notebook;
+mod offline;
 mod page;
+mod progress;
 mod section;
+mod serve;
+mod sitemap;
+mod stats;
+mod status;
+mod strings;
 mod templates;
+mod timings;
 mod utils;
+mod watch;
 
 #[cfg(feature = "backtrace")]
 fn main() {
-    if let Err(e) = _main() {
-        eprintln!("{:?}", e);
-
-        if let Some(bt) = e
-            .downcast_ref::<onenote_parser::errors::Error>()
-            .and_then(std::error::Error::backtrace)
-        {
-            eprintln!();
-            eprintln!("Caused by:");
-            eprintln!("{}", bt)
-        }
+    match panic::catch_unwind(_main) {
+        Ok(Ok(code)) => exit(code),
+        Ok(Err(e)) => {
+            let code = error_code(&e);
+            diagnostics::print_error(&e, code);
+
+            if let Some(bt) = e
+                .downcast_ref::<onenote_parser::errors::Error>()
+                .and_then(std::error::Error::backtrace)
+            {
+                eprintln!();
+                eprintln!("Caused by:");
+                eprintln!("{}", bt)
+            }
 
-        exit(1);
+            exit(code);
+        }
+        // The panic hook has already reported the panic
+        Err(_) => exit(status::EXIT_ERROR),
     }
 }
 
 #[cfg(not(feature = "backtrace"))]
 fn main() {
-    if let Err(e) = _main() {
-        eprintln!("{:?}", e);
+    match panic::catch_unwind(_main) {
+        Ok(Ok(code)) => exit(code),
+        Ok(Err(e)) => {
+            let code = error_code(&e);
+            diagnostics::print_error(&e, code);
+
+            exit(code);
+        }
+        // The panic hook has already reported the panic
+        Err(_) => exit(status::EXIT_ERROR),
+    }
+}
 
-        exit(1);
+/// Determine the exit code for an error from the errors that caused it.
+fn error_code(e: &Report) -> i32 {
+    if e.chain()
+        .any(|cause| cause.is::<onenote_parser::errors::Error>())
+    {
+        status::EXIT_PARSE_ERROR
+    } else if e.chain().any(|cause| cause.is::<std::io::Error>()) {
+        status::EXIT_IO_ERROR
+    } else {
+        status::EXIT_ERROR
     }
 }
 
-fn _main() -> Result<()> {
-    happylog::initialize(LevelFilter::Info)?;
+/// Run the conversion and return the exit code.
+fn _main() -> Result<i32> {
+    if std::env::args_os()
+        .nth(1)
+        .map_or(false, |arg| arg == "diff")
+    {
+        let opt = DiffOpt::from_iter(std::env::args_os().skip(1));
+
+        happylog::initialize(LevelFilter::Info)?;
+        color_eyre::install()?;
+        strings::init(&opt.lang, None)?;
 
-    let opt: Opt = Opt::from_args();
+        diff::diff(&opt)?;
+
+        return Ok(status::EXIT_SUCCESS);
+    }
+
+    let mut opt: Opt = Opt::from_args();
+
+    match opt.message_format {
+        MessageFormat::Human => happylog::initialize(LevelFilter::Info)?,
+        MessageFormat::Json => diagnostics::init_json()?,
+    }
+
+    if opt.progress == ProgressFormat::Json {
+        progress::init_json();
+    }
 
     color_eyre::install()?;
 
-    let output_dir = opt.output;
+    strings::init(&opt.lang, opt.strings.as_deref())?;
+    templates::theme::init(opt.theme);
+    templates::robots::init(opt.noindex);
+
+    if let Some(path) = &opt.note_tag_map {
+        page::note_tag_map::init(path)?;
+    }
+
+    if let Some(path) = &opt.redact {
+        page::redaction::init(path)?;
+    }
+
+    if let Some(path) = &opt.replace {
+        page::replacement::init(path)?;
+    }
+
+    if let Some(path) = &opt.favicon_image {
+        favicon::init(path)?;
+    }
+
+    if let Some(path) = &opt.footer {
+        page::footer::init(path, opt.deterministic)?;
+    }
+
+    #[cfg(feature = "scripting")]
+    if let Some(path) = &opt.script {
+        page::script::init(path)?;
+    }
+
+    if opt.list {
+        for input in &opt.input {
+            match download::url(input) {
+                Some(url) => {
+                    let path = download::download(url, &opt.headers)?;
+                    let result = list::list(&path);
+                    download::remove(&path)?;
+
+                    result?;
+                }
+                None => list::list(input)?,
+            }
+        }
+
+        return Ok(status::EXIT_SUCCESS);
+    }
+
+    if let Some(dir) = &opt.merge_into {
+        if !dir.is_dir() {
+            return Err(eyre!(
+                "Output directory to merge into does not exist: {}",
+                dir.display()
+            ));
+        }
+
+        opt.output = Some(dir.clone());
+    }
+
+    if opt.output.is_none() && opt.serve.is_some() {
+        opt.output = Some(std::env::temp_dir().join(format!("one2html-{}", std::process::id())));
+    }
+
+    let output_dir = opt
+        .output
+        .as_deref()
+        .wrap_err("No output directory given")?;
     assert!(!output_dir.is_file());
 
-    for path in opt.input {
-        convert(&path, &output_dir)?;
+    if opt.merge.is_some() && opt.format != OutputFormat::Html {
+        return Err(eyre!("Merged documents can only be written as HTML"));
+    }
+
+    if !opt.resume {
+        checkpoint::clear(output_dir)?;
+    }
+
+    let skipped = convert_inputs(&opt, output_dir)?;
+
+    let server = match opt.serve {
+        Some(addr) => Some(serve::start(output_dir, addr, opt.watch)?),
+        None => None,
+    };
+
+    if opt.watch {
+        watch::watch(&opt.input, || match convert_inputs(&opt, output_dir) {
+            Ok(_) => {
+                if let Some(server) = &server {
+                    server.reload();
+                }
+            }
+            Err(e) => diagnostics::print_error(&e, error_code(&e)),
+        })?;
+    }
+
+    if let Some(server) = server {
+        server.wait()?;
+    }
+
+    if opt.timings {
+        timings::print_report();
+    }
+
+    if opt.page.is_some() && status::matched_pages() == 0 {
+        warn!(target: status::NO_MATCH, "No page matched the page filter");
+
+        return Ok(status::EXIT_NO_MATCH);
+    }
+
+    if !skipped.is_empty() || status::failed_pages() > 0 {
+        return Ok(status::EXIT_PARTIAL);
+    }
+
+    Ok(status::EXIT_SUCCESS)
+}
+
+/// Convert all input files and return the names of sections that couldn't
+/// be read.
+fn convert_inputs(opt: &Opt, output_dir: &Path) -> Result<Vec<String>> {
+    status::set_current_page(None);
+    nav::clear();
+    stats::clear();
+    inventory::clear();
+
+    let mut skipped = Vec::new();
+
+    for input in &opt.input {
+        match download::url(input) {
+            Some(url) => {
+                let path = download::download(url, &opt.headers)?;
+                let result = convert_cached(&path, output_dir, opt, &mut skipped);
+                let removed = download::remove(&path);
+
+                // A failed conversion is more important than a leftover file
+                result?;
+                removed?;
+            }
+            None => convert_cached(input, output_dir, opt, &mut skipped)?,
+        }
+    }
+
+    if let Some(base_url) = &opt.base_url {
+        sitemap::write(output_dir, base_url)?;
+    }
+
+    if (opt.input.len() > 1 || opt.merge_into.is_some())
+        && opt.merge.is_none()
+        && opt.format == OutputFormat::Html
+    {
+        index::write(output_dir)?;
+    }
+
+    if opt.robots_txt {
+        sitemap::write_robots_txt(output_dir)?;
+    }
+
+    if opt.nav_json {
+        nav::write(output_dir, opt.merge_into.is_some())?;
+    }
+
+    if opt.stats {
+        stats::write(output_dir)?;
+    }
+
+    if let Some(path) = &opt.inventory {
+        inventory::write(path)?;
+    }
+
+    if opt.offline {
+        offline::write(output_dir)?;
+    }
+
+    if opt.checksums {
+        checksums::write(output_dir)?;
+    }
+
+    checkpoint::clear(output_dir)?;
+
+    if opt.redact.is_some() {
+        println!("Made {} redaction(s)", page::redaction::take_total());
+    }
+
+    if !skipped.is_empty() {
+        println!(
+            "Skipped {} section(s) that couldn't be read:",
+            skipped.len()
+        );
+
+        for name in &skipped {
+            println!("  {}", style(name).bright());
+        }
+    }
+
+    Ok(skipped)
+}
+
+/// Convert the section or notebook at `path`, unless it's unchanged since it
+/// has been converted the last time.
+fn convert_cached(
+    path: &Path,
+    output_dir: &Path,
+    opt: &Opt,
+    skipped: &mut Vec<String>,
+) -> Result<()> {
+    // The navigation, statistics and inventory are collected while rendering,
+    // so unchanged inputs have to be converted again to be included
+    let collects_pages = opt.nav_json || opt.stats || opt.inventory.is_some();
+
+    let digest = match &opt.cache_dir {
+        Some(cache_dir) if !collects_pages => Some((cache_dir, cache::digest(path, opt)?)),
+        _ => None,
+    };
+
+    if let Some((cache_dir, digest)) = digest {
+        if let Some(matched_pages) = cache::is_fresh(cache_dir, path, digest)? {
+            println!("Skipping unchanged {}", style(path.display()).bright());
+            status::pages_matched(matched_pages);
+
+            return Ok(());
+        }
+    }
+
+    let matched_pages = status::matched_pages();
+    let skipped_sections = skipped.len();
+
+    let output = convert(path, output_dir, opt, skipped)?;
+
+    // Inputs with skipped sections are converted again to report them again
+    if let Some(((cache_dir, digest), output)) = digest.zip(output) {
+        if skipped.len() == skipped_sections {
+            cache::store(
+                cache_dir,
+                path,
+                digest,
+                &output,
+                status::matched_pages() - matched_pages,
+            )?;
+        }
     }
 
     Ok(())
 }
 
-fn convert(path: &Path, output_dir: &Path) -> Result<()> {
+/// Convert the section or notebook at `path` and return the directory it has
+/// been converted to. The names of sections that couldn't be parsed (e.g.
+/// because they're password-protected) are added to `skipped`.
+fn convert(
+    path: &Path,
+    output_dir: &Path,
+    opt: &Opt,
+    skipped: &mut Vec<String>,
+) -> Result<Option<PathBuf>> {
     let mut parser = Parser::new();
 
     match path.extension().map(|p| p.to_string_lossy()).as_deref() {
@@ -70,9 +381,32 @@ fn convert(path: &Path, output_dir: &Path) -> Result<()> {
             let name = path.file_name().unwrap_or_default().to_string_lossy();
             println!("Processing section {}...", style(&name).bright());
 
-            let section = with_progress("Parsing input file...", || parser.parse_section(&path))?;
+            // Fail on unreadable files before the parser can report them
+            fs::File::open(path).wrap_err_with(|| format!("Failed to open {}", name))?;
 
-            section::Renderer::new().render(&section, output_dir)?;
+            let start = Instant::now();
+            let result = with_progress("Parsing input file...", || parser.parse_section(&path));
+            timings::record_parse(&name, start.elapsed());
+            let section = match result {
+                Ok(section) => section,
+                // A single section that can't be read fails the conversion
+                Err(err) if section::is_unreadable(&err) && opt.input.len() > 1 => {
+                    warn!(
+                        target: status::SKIPPED,
+                        "Skipping section {}, it may be password-protected: {}",
+                        name, err
+                    );
+                    skipped.push(name.to_string());
+
+                    return Ok(None);
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut renderer = section::Renderer::new(opt.clone());
+            renderer.set_source(path.to_path_buf());
+
+            Ok(Some(renderer.render(&section, output_dir)?))
         }
         Some("onetoc2") => {
             let name = path
@@ -83,24 +417,72 @@ fn convert(path: &Path, output_dir: &Path) -> Result<()> {
                 .to_string_lossy();
             println!("Processing notebook {}...", style(&name).bright());
 
-            let notebook = with_progress("[1/2] Parsing input files...", || {
-                parser.parse_notebook(&path)
-            })?;
-
-            let notebook_name = path
-                .parent()
-                .wrap_err("Input file has no parent folder")?
+            let notebook_dir = path.parent().wrap_err("Input file has no parent folder")?;
+            let notebook_name = notebook_dir
                 .file_name()
                 .wrap_err("Parent folder has no name")?
                 .to_string_lossy();
 
+            let notebook_output = output_dir.join(sanitize_filename::sanitize(&notebook_name));
+
+            // Render the sections one at a time, skipping unreadable ones
+            let mut render_files = || {
+                let mut renderer = notebook::Renderer::new(opt.clone());
+                let result = with_progress("Rendering sections...", || {
+                    renderer.render_files(&notebook_name, notebook_dir, output_dir)
+                });
+                skipped.extend_from_slice(renderer.skipped());
+
+                result
+            };
+
+            if opt.low_memory {
+                if opt.merge == Some(MergeMode::Notebook) {
+                    return Err(eyre!(
+                        "Merging notebooks isn't supported in low-memory mode"
+                    ));
+                }
+
+                render_files()?;
+
+                return Ok(Some(notebook_output));
+            }
+
+            let start = Instant::now();
+            let result = with_progress("[1/2] Parsing input files...", || {
+                parser.parse_notebook(&path)
+            });
+            timings::record_parse(&name, start.elapsed());
+
+            let notebook = match result {
+                Ok(notebook) => notebook,
+                Err(err)
+                    if section::is_unreadable(&err) && opt.merge != Some(MergeMode::Notebook) =>
+                {
+                    warn!(
+                        "Failed to parse notebook {}, converting its sections one by one: {}",
+                        name, err
+                    );
+
+                    render_files()?;
+
+                    return Ok(Some(notebook_output));
+                }
+                Err(err) => return Err(err.into()),
+            };
+
             with_progress("[2/2] Rendering sections...", || {
-                notebook::Renderer::new().render(&notebook, &notebook_name, &output_dir)
+                notebook::Renderer::new(opt.clone()).render(
+                    &notebook,
+                    &notebook_name,
+                    notebook_dir,
+                    output_dir,
+                )
             })?;
+
+            Ok(Some(notebook_output))
         }
-        Some(ext) => return Err(eyre!("Invalid file extension: {}", ext)),
-        _ => return Err(eyre!("Couldn't determine file type")),
+        Some(ext) => Err(eyre!("Invalid file extension: {}", ext)),
+        _ => Err(eyre!("Couldn't determine file type")),
     }
-
-    Ok(())
 }
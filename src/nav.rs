@@ -0,0 +1,244 @@
+use crate::utils::json_string;
+use color_eyre::eyre::{Result, WrapErr};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+static SECTIONS: Lazy<Mutex<Vec<NavSection>>> = Lazy::new(Default::default);
+
+/// File that keeps the recorded sections of previous runs, so new sections
+/// can be merged into their navigation.
+pub(crate) const STATE_FILE_NAME: &str = ".one2html-nav.toml";
+
+/// A converted section. All paths are URL paths relative to the output
+/// directory.
+pub(crate) struct NavSection {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) index: String,
+    pub(crate) pages: Vec<NavPage>,
+}
+
+pub(crate) struct NavPage {
+    pub(crate) title: String,
+    pub(crate) level: i32,
+    pub(crate) path: String,
+    pub(crate) tags: Vec<String>,
+}
+
+#[derive(Default)]
+struct Node<'a> {
+    name: String,
+    path: String,
+    section: Option<&'a NavSection>,
+    children: Vec<Node<'a>>,
+}
+
+/// Remember a converted section for the navigation file.
+pub(crate) fn record_section(section: NavSection) {
+    let mut sections = SECTIONS.lock().expect("navigation lock poisoned");
+
+    sections.push(section);
+}
+
+/// Forget the recorded sections, e.g. before converting the inputs again.
+pub(crate) fn clear() {
+    SECTIONS.lock().expect("navigation lock poisoned").clear();
+}
+
+/// Write a `nav.json` file describing the hierarchy of all converted
+/// sections and their pages. With `merge`, the sections of previous runs
+/// that haven't been converted again are kept.
+pub(crate) fn write(output_dir: &Path, merge: bool) -> Result<()> {
+    let mut sections = SECTIONS.lock().expect("navigation lock poisoned");
+
+    if merge {
+        let mut previous = load_state(output_dir)?;
+        previous.retain(|section| !sections.iter().any(|s| s.path == section.path));
+        previous.append(&mut sections);
+        *sections = previous;
+    }
+
+    write_state(output_dir, &sections)?;
+
+    let mut root = Node::default();
+
+    for section in sections.iter() {
+        let mut node = &mut root;
+
+        for segment in section
+            .path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+        {
+            let name = percent_decode_str(segment).decode_utf8_lossy().to_string();
+            let index = match node.children.iter().position(|child| child.name == name) {
+                Some(index) => index,
+                None => {
+                    let path = match node.path.as_str() {
+                        "" => segment.to_string(),
+                        parent => format!("{}/{}", parent, segment),
+                    };
+
+                    node.children.push(Node {
+                        name,
+                        path,
+                        ..Node::default()
+                    });
+                    node.children.len() - 1
+                }
+            };
+
+            node = &mut node.children[index];
+        }
+
+        node.section = Some(section);
+    }
+
+    let json = format!("{{\"children\":{}}}\n", render_nodes(&root.children));
+
+    fs::write(output_dir.join("nav.json"), json).wrap_err("Failed to write navigation file")
+}
+
+fn load_state(output_dir: &Path) -> Result<Vec<NavSection>> {
+    let path = output_dir.join(STATE_FILE_NAME);
+
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let state: toml::Value = fs::read_to_string(path)
+        .wrap_err("Failed to read navigation state")?
+        .parse()
+        .wrap_err("Failed to parse navigation state")?;
+
+    Ok(array_field(&state, "sections")
+        .iter()
+        .map(|section| NavSection {
+            name: string_field(section, "name"),
+            path: string_field(section, "path"),
+            index: string_field(section, "index"),
+            pages: array_field(section, "pages")
+                .iter()
+                .map(|page| NavPage {
+                    title: string_field(page, "title"),
+                    level: page
+                        .get("level")
+                        .and_then(|level| level.as_integer())
+                        .unwrap_or(1) as i32,
+                    path: string_field(page, "path"),
+                    tags: array_field(page, "tags")
+                        .iter()
+                        .filter_map(|tag| tag.as_str())
+                        .map(|tag| tag.to_string())
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+fn write_state(output_dir: &Path, sections: &[NavSection]) -> Result<()> {
+    let sections = sections
+        .iter()
+        .map(|section| {
+            let pages = section
+                .pages
+                .iter()
+                .map(|page| {
+                    let mut table = toml::value::Table::new();
+                    table.insert("title".to_string(), page.title.clone().into());
+                    table.insert("level".to_string(), i64::from(page.level).into());
+                    table.insert("path".to_string(), page.path.clone().into());
+                    table.insert("tags".to_string(), page.tags.clone().into());
+
+                    toml::Value::Table(table)
+                })
+                .collect_vec();
+
+            let mut table = toml::value::Table::new();
+            table.insert("name".to_string(), section.name.clone().into());
+            table.insert("path".to_string(), section.path.clone().into());
+            table.insert("index".to_string(), section.index.clone().into());
+            table.insert("pages".to_string(), toml::Value::Array(pages));
+
+            toml::Value::Table(table)
+        })
+        .collect_vec();
+
+    let mut state = toml::value::Table::new();
+    state.insert("sections".to_string(), toml::Value::Array(sections));
+
+    let state = toml::to_string(&state).wrap_err("Failed to serialize navigation state")?;
+
+    fs::write(output_dir.join(STATE_FILE_NAME), state).wrap_err("Failed to write navigation state")
+}
+
+fn string_field(value: &toml::Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn array_field<'a>(value: &'a toml::Value, key: &str) -> &'a [toml::Value] {
+    value
+        .get(key)
+        .and_then(|value| value.as_array())
+        .map_or(&[][..], |values| values.as_slice())
+}
+
+fn render_nodes(nodes: &[Node]) -> String {
+    format!("[{}]", nodes.iter().map(render_node).join(","))
+}
+
+fn render_node(node: &Node) -> String {
+    let name = node.section.map_or(&node.name, |section| &section.name);
+    let mut fields = vec![
+        ("name", json_string(name)),
+        ("path", json_string(&node.path)),
+    ];
+
+    if let Some(section) = node.section {
+        fields.push(("index", json_string(&section.index)));
+        fields.push((
+            "pages",
+            format!("[{}]", section.pages.iter().map(render_page).join(",")),
+        ));
+    }
+
+    if !node.children.is_empty() {
+        fields.push(("children", render_nodes(&node.children)));
+    }
+
+    render_object(&fields)
+}
+
+fn render_page(page: &NavPage) -> String {
+    render_object(&[
+        ("title", json_string(&page.title)),
+        ("level", page.level.to_string()),
+        ("path", json_string(&page.path)),
+        (
+            "tags",
+            format!(
+                "[{}]",
+                page.tags.iter().map(|tag| json_string(tag)).join(",")
+            ),
+        ),
+    ])
+}
+
+fn render_object(fields: &[(&str, String)]) -> String {
+    format!(
+        "{{{}}}",
+        fields
+            .iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), value))
+            .join(",")
+    )
+}
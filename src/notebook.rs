@@ -1,24 +1,97 @@
+use crate::cli::{MergeMode, Opt};
+use crate::strings::strings;
+use crate::templates::merged;
 use crate::templates::notebook::Toc;
-use crate::{section, templates};
-use color_eyre::eyre::{eyre, Result};
+use crate::timings;
+use crate::utils::slugify;
+use crate::{favicon, section, status, templates};
+use color_eyre::eyre::{Result, WrapErr};
+use itertools::Itertools;
+use log::warn;
 use onenote_parser::notebook::Notebook;
 use onenote_parser::property::common::Color;
 use onenote_parser::section::{Section, SectionEntry};
+use onenote_parser::Parser;
 use palette::rgb::Rgb;
 use palette::{Alpha, ConvertFrom, Hsl, Saturate, Shade, Srgb};
+use std::ffi::OsStr;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// The name of the section group that holds deleted pages.
+const RECYCLE_BIN: &str = "OneNote_RecycleBin";
 
 pub(crate) type RgbColor = Alpha<Rgb<palette::encoding::Srgb, u8>, f32>;
 
-pub(crate) struct Renderer;
+pub(crate) struct Renderer {
+    opt: Opt,
+    /// The name of the notebook that's being rendered.
+    name: String,
+    /// The names of the sections that couldn't be read.
+    skipped: Vec<String>,
+}
 
 impl Renderer {
-    pub fn new() -> Self {
-        Renderer
+    pub fn new(opt: Opt) -> Self {
+        Renderer {
+            opt,
+            name: String::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// The names of the sections that were skipped because they couldn't be
+    /// read, e.g. because they're password-protected.
+    pub(crate) fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    pub fn render(
+        &mut self,
+        notebook: &Notebook,
+        name: &str,
+        source_dir: &Path,
+        output_dir: &Path,
+    ) -> Result<()> {
+        self.name = name.to_string();
+        let notebook_dir = self.notebook_dir(name, output_dir)?;
+
+        if self.opt.merge == Some(MergeMode::Notebook) {
+            let entries = self.render_merged_entries(
+                notebook.entries(),
+                &notebook_dir,
+                output_dir,
+                0,
+                &mut merged::Anchors::default(),
+            )?;
+
+            let html = merged::render(name, &entries, None)?;
+            fs::write(output_dir.join(format!("{}.html", name)), html)?;
+
+            return Ok(());
+        }
+
+        let toc = self.render_entries(notebook.entries(), source_dir, &notebook_dir)?;
+
+        self.write_toc(name, toc, &notebook_dir, output_dir)
+    }
+
+    /// Render the notebook stored in `source_dir` by parsing and rendering its
+    /// section files one at a time, so that only a single section is kept in
+    /// memory.
+    ///
+    /// The notebook's table of contents isn't parsed, so sections and section
+    /// groups are ordered by their file names.
+    pub fn render_files(&mut self, name: &str, source_dir: &Path, output_dir: &Path) -> Result<()> {
+        self.name = name.to_string();
+        let notebook_dir = self.notebook_dir(name, output_dir)?;
+        let toc = self.render_files_in(source_dir, &notebook_dir)?;
+
+        self.write_toc(name, toc, &notebook_dir, output_dir)
     }
 
-    pub fn render(&mut self, notebook: &Notebook, name: &str, output_dir: &Path) -> Result<()> {
+    fn notebook_dir(&self, name: &str, output_dir: &Path) -> Result<PathBuf> {
         if !output_dir.is_dir() {
             fs::create_dir(&output_dir)?;
         }
@@ -29,67 +102,269 @@ impl Renderer {
             fs::create_dir(&notebook_dir)?;
         }
 
+        Ok(notebook_dir)
+    }
+
+    fn write_toc(
+        &self,
+        name: &str,
+        toc: Vec<Toc>,
+        notebook_dir: &Path,
+        output_dir: &Path,
+    ) -> Result<()> {
+        let dir_name = notebook_dir.strip_prefix(output_dir)?;
+
+        let toc = toc
+            .into_iter()
+            .map(|entry| entry.nest(dir_name, 0))
+            .collect_vec();
+
+        let favicon_dir = if self.opt.favicon {
+            favicon::write(notebook_dir, None)?;
+
+            Some(format!("{}/", dir_name.to_string_lossy()))
+        } else {
+            None
+        };
+
+        let toc_html = templates::notebook::render(name, &toc, favicon_dir)?;
+        let toc_file = output_dir.join(format!("{}.html", name));
+        fs::write(toc_file, toc_html)?;
+
+        Ok(())
+    }
+
+    /// Render the sections and section groups in `entries` to `output_dir`.
+    /// Their files are stored in `source_dir`.
+    ///
+    /// The paths in the returned TOC are relative to `output_dir`.
+    fn render_entries(
+        &mut self,
+        entries: &[SectionEntry],
+        source_dir: &Path,
+        output_dir: &Path,
+    ) -> Result<Vec<Toc>> {
+        let mut toc = Vec::new();
+
+        for entry in entries {
+            match entry {
+                SectionEntry::Section(section) => {
+                    toc.push(Toc::Section(
+                        self.render_section(section, source_dir, output_dir)?,
+                    ));
+                }
+                SectionEntry::SectionGroup(group) if self.skip_group(group.display_name()) => {}
+                SectionEntry::SectionGroup(group) => {
+                    let source_dir = source_dir.join(group.display_name());
+
+                    toc.extend(self.render_group(
+                        group.display_name(),
+                        output_dir,
+                        |renderer, group_dir| {
+                            renderer.render_entries(group.entries(), &source_dir, group_dir)
+                        },
+                    )?);
+                }
+            }
+        }
+
+        Ok(toc)
+    }
+
+    /// Render the section files and section group folders in `source_dir` to
+    /// `output_dir`.
+    fn render_files_in(&mut self, source_dir: &Path, output_dir: &Path) -> Result<Vec<Toc>> {
+        let mut paths = fs::read_dir(source_dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.sort();
+
         let mut toc = Vec::new();
 
-        for entry in notebook.entries() {
+        for path in paths {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+            if path.is_dir() {
+                if self.skip_group(&name) {
+                    continue;
+                }
+
+                toc.extend(self.render_group(&name, output_dir, |renderer, group_dir| {
+                    renderer.render_files_in(&path, group_dir)
+                })?);
+            } else if path.extension() == Some(OsStr::new("one")) {
+                let start = Instant::now();
+                let result = Parser::new().parse_section(&path);
+                timings::record_parse(&name, start.elapsed());
+
+                let section = match result {
+                    Ok(section) => section,
+                    Err(err) if section::is_unreadable(&err) => {
+                        warn!(
+                            target: status::SKIPPED,
+                            "Skipping section {}, it may be password-protected: {}",
+                            name, err
+                        );
+                        self.skipped.push(name.to_string());
+
+                        continue;
+                    }
+                    Err(err) => {
+                        return Err(err)
+                            .wrap_err_with(|| format!("Failed to parse section {}", name))
+                    }
+                };
+
+                toc.push(Toc::Section(
+                    self.render_section(&section, source_dir, output_dir)?,
+                ));
+            }
+        }
+
+        Ok(toc)
+    }
+
+    /// Render a section group to its own directory in `output_dir`, using
+    /// `render_entries` to render the group's contents. Returns the TOC
+    /// entries of the group and its contents.
+    fn render_group<F>(
+        &mut self,
+        name: &str,
+        output_dir: &Path,
+        render_entries: F,
+    ) -> Result<Vec<Toc>>
+    where
+        F: FnOnce(&mut Self, &Path) -> Result<Vec<Toc>>,
+    {
+        let dir_name = sanitize_filename::sanitize(name);
+        let group_dir = output_dir.join(&dir_name);
+        if !group_dir.is_dir() {
+            fs::create_dir(&group_dir)?;
+        }
+
+        let entries = render_entries(self, &group_dir)?;
+
+        let index_html = templates::notebook::render(group_name(name), &entries)?;
+        fs::write(group_dir.join("index.html"), index_html)?;
+
+        let mut toc = vec![Toc::SectionGroup(templates::notebook::SectionGroup {
+            name: group_name(name).to_string(),
+            path: dir_name.clone(),
+            level: 0,
+        })];
+        toc.extend(
+            entries
+                .into_iter()
+                .map(|entry| entry.nest(Path::new(&dir_name), 1)),
+        );
+
+        Ok(toc)
+    }
+
+    /// Render the sections and section groups in `entries` as entries of a
+    /// merged document stored in `base_dir`.
+    fn render_merged_entries(
+        &mut self,
+        entries: &[SectionEntry],
+        output_dir: &Path,
+        base_dir: &Path,
+        level: usize,
+        anchors: &mut merged::Anchors,
+    ) -> Result<Vec<merged::Entry>> {
+        let mut merged_entries = Vec::new();
+
+        for entry in entries {
             match entry {
                 SectionEntry::Section(section) => {
-                    toc.push(Toc::Section(self.render_section(
+                    let dir = output_dir.join(section::file_stem(section.display_name()));
+
+                    merged_entries.push(merged::Entry {
+                        title: section.display_name().to_string(),
+                        anchor: anchors
+                            .unique(slugify(&dir.strip_prefix(base_dir)?.to_string_lossy())),
+                        level,
+                        content: String::new(),
+                        styles: Vec::new(),
+                    });
+
+                    let mut renderer = section::Renderer::new(self.opt.clone());
+                    merged_entries.extend(renderer.render_merged_pages(
                         section,
-                        &notebook_dir,
                         output_dir,
-                    )?));
+                        base_dir,
+                        level + 1,
+                        anchors,
+                    )?);
                 }
+                SectionEntry::SectionGroup(group) if self.skip_group(group.display_name()) => {}
                 SectionEntry::SectionGroup(group) => {
-                    let dir_name = sanitize_filename::sanitize(group.display_name());
-                    let group_dir = notebook_dir.join(dir_name);
+                    let group_dir =
+                        output_dir.join(sanitize_filename::sanitize(group.display_name()));
                     if !group_dir.is_dir() {
                         fs::create_dir(&group_dir)?;
                     }
 
-                    let mut entries = Vec::new();
-
-                    for entry in group.entries() {
-                        if let SectionEntry::Section(section) = entry {
-                            entries.push(self.render_section(section, &group_dir, &output_dir)?);
-                        } else {
-                            return Err(eyre!("Nested section groups are not yet supported"));
-                        }
-                    }
+                    merged_entries.push(merged::Entry {
+                        title: group_name(group.display_name()).to_string(),
+                        anchor: anchors.unique(slugify(
+                            &group_dir.strip_prefix(base_dir)?.to_string_lossy(),
+                        )),
+                        level,
+                        content: String::new(),
+                        styles: Vec::new(),
+                    });
 
-                    toc.push(templates::notebook::Toc::SectionGroup(
-                        group.display_name().to_string(),
-                        entries,
-                    ))
+                    merged_entries.extend(self.render_merged_entries(
+                        group.entries(),
+                        &group_dir,
+                        base_dir,
+                        level + 1,
+                        anchors,
+                    )?);
                 }
             }
         }
 
-        let toc_html = templates::notebook::render(name, &toc)?;
-        let toc_file = output_dir.join(format!("{}.html", name));
-        fs::write(toc_file, toc_html)?;
+        Ok(merged_entries)
+    }
 
-        Ok(())
+    /// Whether the section group is the recycle bin, which is only rendered
+    /// if deleted pages are included.
+    fn skip_group(&self, name: &str) -> bool {
+        name == RECYCLE_BIN && !self.opt.include_deleted
     }
 
     fn render_section(
         &mut self,
         section: &Section,
-        notebook_dir: &Path,
-        base_dir: &Path,
+        source_dir: &Path,
+        output_dir: &Path,
     ) -> Result<templates::notebook::Section> {
-        let mut renderer = section::Renderer::new();
-        let path = renderer.render(section, notebook_dir)?;
+        let mut renderer = section::Renderer::new(self.opt.clone());
+        renderer.set_source(source_dir.join(format!("{}.one", section.display_name())));
+        renderer.set_notebook(self.name.clone());
+
+        let path = renderer.render(section, output_dir)?;
 
         Ok(templates::notebook::Section {
             name: section.display_name().to_string(),
-            path: path.strip_prefix(base_dir)?.to_string_lossy().to_string(),
+            path: path.strip_prefix(output_dir)?.to_string_lossy().to_string(),
             color: section.color().map(prepare_color),
+            level: 0,
         })
     }
 }
 
-fn prepare_color(color: Color) -> RgbColor {
+/// The name of a section group as shown in the navigation.
+fn group_name(name: &str) -> &str {
+    if name == RECYCLE_BIN {
+        &strings().deleted_pages
+    } else {
+        name
+    }
+}
+
+pub(crate) fn prepare_color(color: Color) -> RgbColor {
     Alpha {
         alpha: color.alpha() as f32 / 255.0,
         color: Srgb::convert_from(
@@ -104,3 +379,10 @@ fn prepare_color(color: Color) -> RgbColor {
         .into_format(),
     }
 }
+
+pub(crate) fn css_color(color: &RgbColor) -> String {
+    format!(
+        "rgba({}, {}, {}, {})",
+        color.color.red, color.color.green, color.color.blue, color.alpha
+    )
+}
@@ -0,0 +1,176 @@
+use crate::cache::{collect_files, hash_file};
+use crate::checkpoint;
+use crate::favicon;
+use crate::nav;
+use crate::utils::{fnv1a, fnv1a_update, json_string, url_path};
+use color_eyre::eyre::{Result, WrapErr};
+use itertools::Itertools;
+use std::fs;
+use std::path::Path;
+
+const MANIFEST_FILE: &str = "manifest.webmanifest";
+const SERVICE_WORKER_FILE: &str = "sw.js";
+
+/// The sizes of the generated app icons.
+const ICON_SIZES: [u32; 2] = [192, 512];
+
+/// Make the converted files in `output_dir` installable as a web app that
+/// can be read offline: write a web app manifest and a service worker that
+/// caches all files, and register them in every HTML file.
+pub(crate) fn write(output_dir: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    collect_files(output_dir, &mut files)?;
+    files.sort();
+
+    for size in ICON_SIZES.iter() {
+        let path = output_dir.join(icon_file_name(*size));
+        favicon::write_icon(&path, *size, favicon::DEFAULT_COLOR)?;
+
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+
+    let mut html_files = Vec::new();
+    let mut cached_files = Vec::new();
+    let mut urls = Vec::new();
+
+    for file in &files {
+        let relative_path = file.strip_prefix(output_dir)?;
+
+        if relative_path == Path::new(checkpoint::FILE_NAME)
+            || relative_path == Path::new(nav::STATE_FILE_NAME)
+            || relative_path == Path::new(MANIFEST_FILE)
+            || relative_path == Path::new(SERVICE_WORKER_FILE)
+        {
+            continue;
+        }
+
+        if file.extension().map_or(false, |ext| ext == "html") {
+            register(file, relative_path.components().count() - 1)?;
+            html_files.push(relative_path);
+        }
+
+        cached_files.push(file);
+        urls.push(format!("./{}", url_path(relative_path)));
+    }
+
+    // Index pages at the top level, e.g. the notebook's table of contents
+    let start_page = html_files
+        .iter()
+        .find(|path| path.components().count() == 1)
+        .or_else(|| html_files.first());
+
+    let name = start_page.and_then(|path| path.file_stem()).map_or_else(
+        || "one2html".to_string(),
+        |name| name.to_string_lossy().to_string(),
+    );
+    let start_url =
+        start_page.map_or_else(|| "./".to_string(), |path| format!("./{}", url_path(path)));
+
+    let manifest = manifest(&name, &start_url);
+    fs::write(output_dir.join(MANIFEST_FILE), &manifest)
+        .wrap_err("Failed to write web app manifest")?;
+    urls.push(format!("./{}", MANIFEST_FILE));
+
+    // Hash the files after registering the service worker in them, so that a
+    // new version is installed whenever any file changes
+    let mut hash = fnv1a_update(fnv1a(urls.join("\n").as_bytes()), manifest.as_bytes());
+
+    for file in cached_files {
+        hash = hash_file(hash, file)?;
+    }
+
+    fs::write(
+        output_dir.join(SERVICE_WORKER_FILE),
+        service_worker(&format!("{:016x}", hash), &urls),
+    )
+    .wrap_err("Failed to write service worker")
+}
+
+fn icon_file_name(size: u32) -> String {
+    format!("icon-{}.png", size)
+}
+
+fn manifest(name: &str, start_url: &str) -> String {
+    let [red, green, blue] = favicon::DEFAULT_COLOR;
+    let color = format!("#{:02x}{:02x}{:02x}", red, green, blue);
+    let icons = ICON_SIZES
+        .iter()
+        .map(|size| {
+            format!(
+                "{{\"src\":{},\"sizes\":\"{}x{}\",\"type\":\"image/png\"}}",
+                json_string(&icon_file_name(*size)),
+                size,
+                size
+            )
+        })
+        .join(",");
+
+    format!(
+        "{{\"name\":{},\"short_name\":{},\"start_url\":{},\"display\":\"standalone\",\"background_color\":\"#ffffff\",\"theme_color\":{},\"icons\":[{}]}}\n",
+        json_string(name),
+        json_string(name),
+        json_string(start_url),
+        json_string(&color),
+        icons
+    )
+}
+
+fn service_worker(version: &str, urls: &[String]) -> String {
+    format!(
+        r#"const CACHE = 'one2html-{}';
+const FILES = [{}];
+
+self.addEventListener('install', (event) => {{
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(FILES)));
+    self.skipWaiting();
+}});
+
+self.addEventListener('activate', (event) => {{
+    event.waitUntil(caches.keys().then((keys) => Promise.all(
+        keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))
+    )));
+}});
+
+self.addEventListener('fetch', (event) => {{
+    event.respondWith(
+        caches.match(event.request, {{ ignoreSearch: true }})
+            .then((response) => response || fetch(event.request))
+    );
+}});
+"#,
+        version,
+        urls.iter().map(|url| json_string(url)).join(",")
+    )
+}
+
+/// Link the manifest and register the service worker in the HTML file at
+/// `path`, which is `depth` directories below the output directory.
+fn register(path: &Path, depth: usize) -> Result<()> {
+    let mut html = fs::read_to_string(path).wrap_err("Failed to read HTML file")?;
+
+    if html.contains(MANIFEST_FILE) {
+        return Ok(());
+    }
+
+    let root = match depth {
+        0 => "./".to_string(),
+        depth => "../".repeat(depth),
+    };
+    let tags = format!(
+        "<link rel=\"manifest\" href=\"{root}{manifest}\">\n<script>if ('serviceWorker' in navigator) navigator.serviceWorker.register('{root}{worker}');</script>\n",
+        root = root,
+        manifest = MANIFEST_FILE,
+        worker = SERVICE_WORKER_FILE
+    );
+
+    // Fragments have no head to add the tags to
+    let position = match html.find("</head>") {
+        Some(position) => position,
+        None => return Ok(()),
+    };
+    html.insert_str(position, &tags);
+
+    fs::write(path, html).wrap_err("Failed to write HTML file")
+}
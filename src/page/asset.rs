@@ -0,0 +1,147 @@
+use crate::cli::{LinkMode, Opt};
+use crate::page::Renderer;
+use crate::progress;
+use crate::strings::strings;
+use crate::templates::html_escape;
+use crate::utils::{content_filename, format_size};
+use color_eyre::eyre::{ContextCompat, WrapErr};
+use color_eyre::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const ASSET_STORE: &str = ".assets";
+
+impl<'a> Renderer<'a> {
+    /// Check whether an image or attachment should be written to the output
+    /// directory or replaced by a placeholder.
+    pub(crate) fn should_write_asset(&self, _filename: &str, _data: &[u8]) -> bool {
+        !self.section.opt.no_assets
+    }
+
+    /// Check whether an attachment should be written to the output directory
+    /// or replaced by a placeholder.
+    pub(crate) fn should_write_attachment(&self, filename: &str, data: &[u8]) -> bool {
+        let exceeds_max_size = self
+            .section
+            .opt
+            .max_attachment_size
+            .map(|max_size| data.len() > max_size)
+            .unwrap_or_default();
+
+        let opt = &self.section.opt;
+        let allowed = opt.allow_attachment_types.is_empty()
+            || matches_file_type(filename, &opt.allow_attachment_types);
+        let denied = matches_file_type(filename, &opt.deny_attachment_types);
+
+        !exceeds_max_size && allowed && !denied && self.should_write_asset(filename, data)
+    }
+
+    /// Render a placeholder with the name, size and type of a file that
+    /// hasn't been written to the output directory.
+    pub(crate) fn render_asset_placeholder(&self, filename: &str, data: &[u8]) -> String {
+        let mime = mime_guess::from_path(filename).first_or_octet_stream();
+        let filename = html_escape(filename).unwrap_or_default();
+
+        format!(
+            "<span class=\"asset-placeholder\" title=\"{}\">{} ({}, {})</span>",
+            strings().asset_omitted,
+            filename,
+            format_size(data.len()),
+            mime.essence_str()
+        )
+    }
+
+    /// Write an image or attachment to the page's output directory.
+    pub(crate) fn write_asset(&mut self, filename: &str, data: &[u8]) -> Result<()> {
+        let start = Instant::now();
+
+        // Queued assets are reported by the asset writer once they're written
+        let result = match &self.section.asset_writer {
+            Some(writer) => {
+                writer.write(self.output.clone(), filename, data);
+
+                Ok(())
+            }
+            None => store_asset(&self.section.opt, &self.output, filename, data)
+                .map(|()| progress::asset_written(filename)),
+        };
+
+        self.asset_time += start.elapsed();
+
+        result
+    }
+}
+
+/// Write an asset to the directory `output`, either directly or by linking it
+/// to the asset store.
+pub(crate) fn store_asset(opt: &Opt, output: &Path, filename: &str, data: &[u8]) -> Result<()> {
+    let path = output.join(filename);
+
+    let mode = match opt.link_assets {
+        Some(mode) => mode,
+        None => return fs::write(&path, data).wrap_err("Failed to write asset"),
+    };
+
+    let output_root = opt.output.as_ref().wrap_err("No output directory given")?;
+    let store = output_root.join(ASSET_STORE);
+
+    // Assets may be written by multiple threads, which `create_dir_all` handles
+    fs::create_dir_all(&store)?;
+
+    let stored_name = content_filename(filename, data);
+    let stored = store.join(&stored_name);
+
+    if !stored.is_file() {
+        fs::write(&stored, data).wrap_err("Failed to write asset to asset store")?;
+    }
+
+    if fs::symlink_metadata(&path).is_ok() {
+        fs::remove_file(&path)?;
+    }
+
+    let result = match mode {
+        LinkMode::Hard => fs::hard_link(&stored, &path),
+        LinkMode::Symbolic => {
+            let depth = output.strip_prefix(output_root)?.components().count();
+            let mut target: PathBuf = (0..depth).map(|_| "..").collect();
+            target.push(ASSET_STORE);
+            target.push(&stored_name);
+
+            symlink(&target, &path)
+        }
+    };
+
+    result.wrap_err("Failed to link asset")
+}
+
+/// Check whether a file matches one of the `types`, given as file extensions
+/// (`pdf`), MIME types (`application/pdf`) or MIME type wildcards (`image/*`).
+fn matches_file_type(filename: &str, types: &[String]) -> bool {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    let mime = mime_guess::from_path(filename).first_or_octet_stream();
+
+    types.iter().any(|file_type| {
+        let file_type = file_type.trim().trim_start_matches('.').to_lowercase();
+
+        if let Some(top_level) = file_type.strip_suffix("/*") {
+            mime.type_() == top_level
+        } else if file_type.contains('/') {
+            mime.essence_str() == file_type
+        } else {
+            extension.as_deref() == Some(file_type.as_str())
+        }
+    })
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, path)
+}
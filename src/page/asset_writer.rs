@@ -0,0 +1,77 @@
+use crate::cli::Opt;
+use crate::page::asset::store_asset;
+use crate::progress;
+use color_eyre::eyre::{eyre, Result};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+
+struct Job {
+    output: PathBuf,
+    filename: String,
+    data: Vec<u8>,
+}
+
+/// Writes assets on a pool of worker threads so that rendering doesn't have
+/// to wait for them.
+pub(crate) struct AssetWriter {
+    sender: Sender<Job>,
+    workers: Vec<JoinHandle<Result<()>>>,
+}
+
+impl AssetWriter {
+    pub(crate) fn new(opt: &Opt, threads: usize) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let opt = Arc::new(opt.clone());
+
+        let workers = (0..threads)
+            .map(|_| {
+                let receiver = receiver.clone();
+                let opt = opt.clone();
+
+                thread::spawn(move || loop {
+                    let job = receiver.lock().expect("asset queue lock poisoned").recv();
+
+                    match job {
+                        Ok(job) => {
+                            store_asset(&opt, &job.output, &job.filename, &job.data)?;
+                            progress::asset_written(&job.filename);
+                        }
+                        Err(_) => return Ok(()),
+                    }
+                })
+            })
+            .collect();
+
+        AssetWriter { sender, workers }
+    }
+
+    /// Queue an asset to be written to the directory `output`.
+    pub(crate) fn write(&self, output: PathBuf, filename: &str, data: &[u8]) {
+        let job = Job {
+            output,
+            filename: filename.to_string(),
+            data: data.to_vec(),
+        };
+
+        // Sending only fails if all workers have stopped because of an error,
+        // which is reported by `finish`
+        let _ = self.sender.send(job);
+    }
+
+    /// Wait until all queued assets have been written.
+    pub(crate) fn finish(self) -> Result<()> {
+        drop(self.sender);
+
+        for worker in self.workers {
+            worker
+                .join()
+                .map_err(|_| eyre!("Asset writer thread panicked"))??;
+        }
+
+        Ok(())
+    }
+}
@@ -5,12 +5,17 @@ use onenote_parser::contents::Content;
 
 impl<'a> Renderer<'a> {
     pub(crate) fn render_content(&mut self, content: &Content) -> Result<String> {
+        // File printouts only link to a file that directly precedes them
+        if let Content::RichText(_) | Content::Table(_) | Content::Unknown = content {
+            self.printout_source = None;
+        }
+
         match content {
             Content::RichText(text) => self.render_rich_text(text),
             Content::Image(image) => self.render_image(image),
             Content::EmbeddedFile(file) => self.render_embedded_file(file),
             Content::Table(table) => self.render_table(table),
-            Content::Ink(ink) => Ok(self.render_ink(ink, None, false)),
+            Content::Ink(ink) => self.render_ink(ink, None, false),
             Content::Unknown => {
                 warn!("Page with unknown content");
 
@@ -0,0 +1,78 @@
+use crate::strings::strings;
+use crate::templates::html_escape;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use mailparse::{MailHeaderMap, ParsedMail};
+use std::path::Path;
+
+/// The headers shown above an email's text.
+const HEADERS: &[&str] = &["From", "To", "Cc", "Date", "Subject"];
+
+/// Whether the file is an email or a web archive printout, i.e. a MIME message.
+pub(crate) fn is_email(filename: &str) -> bool {
+    Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| {
+            matches!(ext.to_ascii_lowercase().as_str(), "eml" | "mht" | "mhtml")
+        })
+}
+
+/// Render an attached email's headers and text, followed by a link to the
+/// original file at `url`. The headers and text are passed through
+/// `text_rules` (replacement and redaction).
+pub(crate) fn render_email(
+    data: &[u8],
+    url: &str,
+    mut text_rules: impl FnMut(String) -> String,
+) -> Result<String> {
+    let mail = mailparse::parse_mail(data).map_err(|e| eyre!("Invalid email: {}", e))?;
+
+    let mut content = String::from("<div class=\"embedded-email\"><dl class=\"email-headers\">");
+
+    for name in HEADERS {
+        if let Some(value) = mail.headers.get_first_value(name) {
+            content.push_str(&format!(
+                "<dt>{}</dt><dd>{}</dd>",
+                name,
+                html_escape(&text_rules(value))?
+            ));
+        }
+    }
+
+    content.push_str("</dl><div class=\"email-body\">");
+    content.push_str(&render_body(&mail, text_rules)?);
+    content.push_str(&format!(
+        "</div><a class=\"email-source\" href=\"{}\">{}</a></div>",
+        url,
+        html_escape(&strings().original_email)?
+    ));
+
+    Ok(content)
+}
+
+/// Render the HTML part of the message (sanitized), or its plain text part.
+fn render_body(mail: &ParsedMail, mut text_rules: impl FnMut(String) -> String) -> Result<String> {
+    if let Some(part) = find_part(mail, "text/html") {
+        let body = part.get_body().wrap_err("Failed to decode email body")?;
+
+        return Ok(ammonia::clean(&text_rules(body)));
+    }
+
+    if let Some(part) = find_part(mail, "text/plain") {
+        let body = part.get_body().wrap_err("Failed to decode email body")?;
+
+        return Ok(format!("<pre>{}</pre>", html_escape(&text_rules(body))?));
+    }
+
+    Ok(String::new())
+}
+
+fn find_part<'a, 'b>(mail: &'a ParsedMail<'b>, mime_type: &str) -> Option<&'a ParsedMail<'b>> {
+    if mail.ctype.mimetype == mime_type {
+        return Some(mail);
+    }
+
+    mail.subparts
+        .iter()
+        .find_map(|part| find_part(part, mime_type))
+}
@@ -1,30 +1,132 @@
-use crate::page::Renderer;
+use crate::cli::{HtmlAttachmentMode, OutputFormat};
+use crate::export::dokuwiki;
+use crate::page::{email, Renderer};
+use crate::templates::html_escape;
+use crate::utils::{content_filename, format_size, px, AttributeSet, StyleSet};
 use color_eyre::eyre::{ContextCompat, WrapErr};
 use color_eyre::Result;
+use infer::MatcherType;
+use log::warn;
 use onenote_parser::contents::EmbeddedFile;
 use onenote_parser::property::embedded_file::FileType;
-use std::fs;
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
 
 impl<'a> Renderer<'a> {
     pub(crate) fn render_embedded_file(&mut self, file: &EmbeddedFile) -> Result<String> {
         let content;
 
-        let filename = self.determine_filename(file.filename())?;
-        fs::write(self.output.join(filename.clone()), file.data())
+        if !self.should_write_attachment(file.filename(), file.data()) {
+            let placeholder = self.render_asset_placeholder(file.filename(), file.data());
+
+            return Ok(self.render_with_note_tags(file.note_tags(), placeholder));
+        }
+
+        let original_filename = with_sniffed_extension(file.filename(), file.data());
+
+        let filename = if self.section.opt.hash_assets {
+            self.hashed_filename(&original_filename, file.data())
+        } else {
+            self.determine_filename(&original_filename)?
+        };
+        self.write_asset(&filename, file.data())
             .wrap_err("Failed to write embedded file")?;
 
         let file_type = Self::guess_type(file);
 
+        let mut attrs = AttributeSet::new();
+        attrs.set("src", self.asset_url(&filename));
+        self.printout_source = Some(self.asset_url(&filename));
+        self.set_data_attributes(&mut attrs, "embedded-file");
+
+        let html_mode = self.section.opt.html_attachments;
+
         match file_type {
-            FileType::Audio => content = format!("<audio controls src=\"{}\"></audio>", filename),
-            FileType::Video => content = format!("<video controls src=\"{}\"></video>", filename),
-            FileType::Unknown => content = format!("<embed src=\"{}\" />", filename),
+            FileType::Audio => content = format!("<audio controls {}></audio>", attrs),
+            FileType::Video => content = format!("<video controls {}></video>", attrs),
+            FileType::Unknown if is_image(&original_filename) => {
+                attrs.set("alt", file.filename().replace('"', "&quot;"));
+                content = format!("<img {} />", attrs)
+            }
+            FileType::Unknown if self.section.opt.emails && email::is_email(file.filename()) => {
+                let url = self.asset_url(&filename);
+                let email =
+                    email::render_email(file.data(), &url, |text| self.apply_text_rules(text));
+
+                content = match email {
+                    Ok(email) => email,
+                    // Offer emails that can't be read as a download instead
+                    Err(err) => {
+                        warn!(
+                            "Failed to render attached email {}: {:#}",
+                            file.filename(),
+                            err
+                        );
+
+                        render_download_card(file.filename(), file.data().len(), &url, attrs)?
+                    }
+                }
+            }
+            FileType::Unknown if is_html(file.filename()) && html_mode.is_some() => {
+                content = match html_mode {
+                    Some(HtmlAttachmentMode::Inline) => {
+                        let html = String::from_utf8_lossy(file.data()).into_owned();
+
+                        format!(
+                            "<div class=\"embedded-html\">{}</div>",
+                            ammonia::clean(&self.apply_text_rules(html))
+                        )
+                    }
+                    _ => format!(
+                        "<iframe class=\"embedded-html\" sandbox {}></iframe>",
+                        attrs
+                    ),
+                }
+            }
+            FileType::Unknown => {
+                let url = self.asset_url(&filename);
+
+                content = render_download_card(file.filename(), file.data().len(), &url, attrs)?
+            }
         };
 
         Ok(self.render_with_note_tags(file.note_tags(), content))
     }
 
+    /// Render an embedded file that's placed directly on the page at its
+    /// position on the page's canvas.
+    pub(crate) fn render_positioned_embedded_file(
+        &mut self,
+        file: &EmbeddedFile,
+    ) -> Result<String> {
+        let content = self.render_embedded_file(file)?;
+
+        let mut attrs = AttributeSet::new();
+        let mut styles = StyleSet::new();
+
+        attrs.set("class", "container-embedded-file".to_string());
+
+        if file.offset_horizontal().is_some() || file.offset_vertical().is_some() {
+            styles.set("position", "absolute".to_string());
+        }
+
+        if let Some(offset) = file.offset_horizontal() {
+            styles.set("left", px(offset));
+        }
+
+        if let Some(offset) = file.offset_vertical() {
+            styles.set("top", px(offset));
+        }
+
+        if let Some(width) = file.layout_max_width() {
+            styles.set("width", px(width));
+        }
+
+        self.set_styles(&mut attrs, styles);
+
+        Ok(format!("<div {}>{}</div>", attrs, content))
+    }
+
     fn guess_type(file: &EmbeddedFile) -> FileType {
         match file.file_type() {
             FileType::Audio => return FileType::Audio,
@@ -43,37 +145,146 @@ impl<'a> Renderer<'a> {
                 return FileType::Video;
             }
         }
-        FileType::Unknown
+
+        // Fall back to the file's contents for renamed or extension-less files
+        match infer::get(file.data()).map(|kind| kind.matcher_type()) {
+            Some(MatcherType::Audio) => FileType::Audio,
+            Some(MatcherType::Video) => FileType::Video,
+            _ => FileType::Unknown,
+        }
     }
 
     pub(crate) fn determine_filename(&mut self, filename: &str) -> Result<String> {
+        // DokuWiki only finds media files with cleaned names
+        let filename = match self.section.opt.format {
+            OutputFormat::DokuWiki => dokuwiki::clean_id(filename),
+            _ => self.wiki_file_name(filename),
+        };
+        let filename = filename.as_str();
+
         let mut i = 0;
         let mut current_filename = filename.to_string();
 
         loop {
-            if !self.section.files.contains(&current_filename) {
-                self.section.files.insert(current_filename.clone());
-
+            if self.section.reserve_file(&current_filename) {
                 return Ok(current_filename);
             }
 
             let path = PathBuf::from(filename);
-            let ext = path
-                .extension()
-                .wrap_err("Embedded file has no extension")?
-                .to_str()
-                .wrap_err("Embedded file name is non utf-8")?;
-            let base = path
-                .as_os_str()
-                .to_str()
-                .wrap_err("Embedded file name is non utf-8")?
-                .strip_suffix(ext)
-                .wrap_err("Failed to strip extension from file name")?
-                .trim_matches('.');
-
-            current_filename = format!("{}-{}.{}", base, i, ext);
+
+            current_filename = match path.extension() {
+                Some(ext) => {
+                    let ext = ext.to_str().wrap_err("Embedded file name is non utf-8")?;
+                    let base = filename
+                        .strip_suffix(ext)
+                        .wrap_err("Failed to strip extension from file name")?
+                        .trim_matches('.');
+
+                    format!("{}-{}.{}", base, i, ext)
+                }
+                None => format!("{}-{}", filename, i),
+            };
 
             i += 1;
         }
     }
+
+    /// Prefix the name of a MediaWiki file with the section's path, as files
+    /// are shared by the whole wiki, e.g. `Notebook - Section - image.png`.
+    pub(crate) fn wiki_file_name(&self, filename: &str) -> String {
+        match self.section.opt.format {
+            OutputFormat::MediaWiki if !self.section_path.is_empty() => {
+                format!("{} - {}", self.section_path.replace('/', " - "), filename)
+            }
+            _ => filename.to_string(),
+        }
+    }
+
+    /// Name a file after the hash of its contents and record its original name
+    /// in the section's asset manifest.
+    pub(crate) fn hashed_filename(&mut self, filename: &str, data: &[u8]) -> String {
+        let hashed = content_filename(filename, data);
+
+        self.section.reserve_file(&hashed);
+        self.section
+            .assets
+            .insert(hashed.clone(), filename.to_string());
+
+        hashed
+    }
+}
+
+/// Image types that browsers can display.
+const BROWSER_IMAGE_TYPES: &[&str] = &["png", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+/// Whether the file is an image, which browsers can display as an image
+/// instead of an embedded object.
+fn is_image(filename: &str) -> bool {
+    mime_guess::from_path(filename)
+        .first()
+        .map_or(false, |mime| {
+            mime.type_() == "image" && BROWSER_IMAGE_TYPES.contains(&mime.subtype().as_str())
+        })
+}
+
+/// Add the extension matching the file's contents if its name has no known
+/// extension, so the file can be opened after it has been written.
+fn with_sniffed_extension<'a>(filename: &'a str, data: &[u8]) -> Cow<'a, str> {
+    if mime_guess::from_path(filename).first().is_some() {
+        return Cow::Borrowed(filename);
+    }
+
+    match infer::get(data) {
+        Some(kind) => Cow::Owned(format!("{}.{}", filename, kind.extension())),
+        None => Cow::Borrowed(filename),
+    }
+}
+
+/// Whether the file is an HTML document, e.g. a clipped web page.
+fn is_html(filename: &str) -> bool {
+    mime_guess::from_path(filename)
+        .first()
+        .map_or(false, |mime| mime.essence_str() == "text/html")
+}
+
+/// Render a link to download an attachment, showing an icon for its type,
+/// its name and its size.
+fn render_download_card(
+    filename: &str,
+    size: usize,
+    url: &str,
+    mut attrs: AttributeSet,
+) -> Result<String> {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    attrs.remove("src");
+    attrs.set("href", url.to_string());
+    attrs.set("class", "attachment".to_string());
+    attrs.set("download", String::new());
+
+    Ok(format!(
+        "<a {}><span class=\"attachment-icon attachment-{}\">{}</span>\
+         <span class=\"attachment-name\">{}</span>\
+         <span class=\"attachment-size\">{}</span></a>",
+        attrs,
+        file_category(&extension),
+        html_escape(&extension.to_uppercase().chars().take(4).collect::<String>())?,
+        html_escape(filename)?,
+        format_size(size)
+    ))
+}
+
+/// The category of a file, used to pick its icon.
+fn file_category(extension: &str) -> &'static str {
+    match extension {
+        "pdf" => "pdf",
+        "doc" | "docx" | "odt" | "rtf" | "txt" | "md" => "document",
+        "xls" | "xlsx" | "ods" | "csv" => "spreadsheet",
+        "ppt" | "pptx" | "odp" => "presentation",
+        "zip" | "7z" | "rar" | "tar" | "gz" => "archive",
+        _ => "file",
+    }
 }
@@ -0,0 +1,110 @@
+use crate::templates::html_escape;
+use color_eyre::eyre::{Result, WrapErr};
+use once_cell::sync::OnceCell;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static FOOTER: OnceCell<String> = OnceCell::new();
+static DATE: OnceCell<String> = OnceCell::new();
+
+/// Load the HTML footer template from `path`. The footer can only be set
+/// once. With `deterministic`, `{date}` doesn't depend on the time of the
+/// conversion.
+pub(crate) fn init(path: &Path, deterministic: bool) -> Result<()> {
+    let footer = fs::read_to_string(path).wrap_err("Failed to read footer template")?;
+
+    let _ = FOOTER.set(footer);
+    let _ = DATE.set(format_timestamp(conversion_time(deterministic)));
+
+    Ok(())
+}
+
+/// The time of the conversion, or `SOURCE_DATE_EPOCH` if it's set. In
+/// deterministic mode, the time defaults to the Unix epoch.
+fn conversion_time(deterministic: bool) -> u64 {
+    let epoch = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok());
+
+    match epoch {
+        Some(epoch) => epoch,
+        None if deterministic => 0,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs()),
+    }
+}
+
+/// Render the footer of a page, if a footer template is set. Placeholders
+/// in the values themselves and unknown placeholders are kept as they are.
+pub(crate) fn render(notebook: Option<&str>, section: &str, page: &str) -> Option<String> {
+    let footer = FOOTER.get()?;
+    let escape = |value: &str| html_escape(value).unwrap_or_default();
+
+    let fields = [
+        ("notebook", escape(notebook.unwrap_or_default())),
+        ("section", escape(section)),
+        ("page", escape(page)),
+        ("date", DATE.get().cloned().unwrap_or_default()),
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+    ];
+
+    let mut output = String::new();
+    let mut rest = footer.as_str();
+
+    while let Some(start) = rest.find('{') {
+        let placeholder = rest[start + 1..]
+            .find('}')
+            .map(|end| &rest[start + 1..start + 1 + end]);
+        let value =
+            placeholder.and_then(|placeholder| fields.iter().find(|(key, _)| *key == placeholder));
+
+        output.push_str(&rest[..start]);
+
+        match (placeholder, value) {
+            (Some(placeholder), Some((_, value))) => {
+                output.push_str(value);
+                rest = &rest[start + placeholder.len() + 2..];
+            }
+            _ => {
+                output.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+
+    Some(output)
+}
+
+/// Format a Unix timestamp as an ISO 8601 date and time in UTC.
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Convert the days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -3,32 +3,57 @@ use crate::utils::{px, AttributeSet, StyleSet};
 use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
 use onenote_parser::contents::Image;
-use std::fs;
+use std::borrow::Cow;
+use std::convert::TryInto;
 
 impl<'a> Renderer<'a> {
     pub(crate) fn render_image(&mut self, image: &Image) -> Result<String> {
         let mut content = String::new();
 
         if let Some(data) = image.data() {
-            let filename = self.determine_image_filename(image)?;
-            fs::write(self.output.join(filename.clone()), data)
+            let data = if self.section.opt.strip_metadata {
+                strip_image_metadata(data)
+            } else {
+                Cow::Borrowed(data)
+            };
+            let data = data.as_ref();
+            let original_filename = self.original_image_filename(image);
+
+            if !self.should_write_asset(&original_filename, data) {
+                content.push_str(&self.render_asset_placeholder(&original_filename, data));
+
+                return Ok(self.render_with_note_tags(image.note_tags(), content));
+            }
+
+            let filename = self.determine_image_filename(image, data)?;
+            self.write_asset(&filename, data)
                 .wrap_err("Failed to write image")?;
+            self.write_thumbnail(data);
 
             let mut attrs = AttributeSet::new();
             let mut styles = StyleSet::new();
 
-            attrs.set("src", filename);
+            attrs.set("src", self.asset_url(&filename));
 
             if let Some(text) = image.alt_text() {
-                attrs.set("alt", text.to_string().replace('"', "&quot;"));
+                let text = self.apply_text_rules(text.to_string());
+                attrs.set("alt", text.replace('"', "&quot;"));
             }
 
+            // OneNote scales images to their layout size, which browsers
+            // only do if it's set as the exact size
+            let (width_property, height_property) = if self.section.opt.exact_layout {
+                ("width", "height")
+            } else {
+                ("max-width", "max-height")
+            };
+
             if let Some(width) = image.layout_max_width() {
-                styles.set("max-width", px(width));
+                styles.set(width_property, px(width));
             }
 
             if let Some(height) = image.layout_max_height() {
-                styles.set("max-height", px(height));
+                styles.set(height_property, px(height));
             }
 
             if image.offset_horizontal().is_some() || image.offset_vertical().is_some() {
@@ -43,37 +68,146 @@ impl<'a> Renderer<'a> {
                 styles.set("top", px(offset));
             }
 
-            if styles.len() > 0 {
-                attrs.set("style", styles.to_string());
+            // File printouts are placed behind the ink that annotates them
+            if image.is_background() {
+                styles.set("z-index", "-1".to_string());
             }
 
-            content.push_str(&format!("<img {} />", attrs.to_string()));
+            self.set_styles(&mut attrs, styles);
+
+            self.set_data_attributes(&mut attrs, "image");
+
+            match image.displayed_page_number() {
+                Some(number) => {
+                    attrs.set("class", "printout-page".to_string());
+                    attrs.set("data-page-number", number.to_string());
+
+                    content.push_str(&self.render_printout_page(attrs));
+                }
+                None => {
+                    self.printout_source = None;
+
+                    content.push_str(&format!("<img {} />", attrs.to_string()))
+                }
+            }
         }
 
         Ok(self.render_with_note_tags(image.note_tags(), content))
     }
 
-    fn determine_image_filename(&mut self, image: &Image) -> Result<String> {
-        if let Some(name) = image.image_filename() {
-            return self.determine_filename(name);
+    /// Render a page of a file printout, linked to the printed file if it's
+    /// attached directly before the printout.
+    fn render_printout_page(&self, attrs: AttributeSet) -> String {
+        match &self.printout_source {
+            Some(url) => format!("<a href=\"{}\"><img {} /></a>", url, attrs),
+            None => format!("<img {} />", attrs),
         }
+    }
 
-        if let Some(ext) = image.extension() {
-            let mut i = 0;
+    fn determine_image_filename(&mut self, image: &Image, data: &[u8]) -> Result<String> {
+        if self.section.opt.hash_assets {
+            return Ok(self.hashed_filename(&self.original_image_filename(image), data));
+        }
 
-            loop {
-                let filename = format!("image{}{}", i, ext);
+        if let Some(name) = self.image_filename(image) {
+            return self.determine_filename(name);
+        }
 
-                if !self.section.files.contains(&filename) {
-                    self.section.files.insert(filename.clone());
+        let ext = image.extension().unwrap_or_default();
+        let mut i = 0;
 
-                    return Ok(filename);
-                }
+        loop {
+            let filename = self.wiki_file_name(&format!("image{}{}", i, ext));
 
-                i += 1;
+            if self.section.reserve_file(&filename) {
+                return Ok(filename);
             }
+
+            i += 1;
+        }
+    }
+
+    /// The image's original file name, unless metadata is stripped.
+    fn image_filename<'i>(&self, image: &'i Image) -> Option<&'i str> {
+        image
+            .image_filename()
+            .filter(|_| !self.section.opt.strip_metadata)
+    }
+
+    fn original_image_filename(&self, image: &Image) -> String {
+        self.image_filename(image)
+            .map(|name| name.to_string())
+            .or_else(|| image.extension().map(|ext| format!("image{}", ext)))
+            .unwrap_or_else(|| "image".to_string())
+    }
+}
+
+/// Remove EXIF, XMP, IPTC and comment segments from JPEG images and text and
+/// EXIF chunks from PNG images. Other images are returned unchanged.
+fn strip_image_metadata(data: &[u8]) -> Cow<[u8]> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    let stripped = if data.starts_with(&[0xff, 0xd8]) {
+        strip_jpeg_metadata(data)
+    } else if data.starts_with(PNG_SIGNATURE) {
+        strip_png_metadata(data, PNG_SIGNATURE.len())
+    } else {
+        None
+    };
+
+    match stripped {
+        Some(stripped) => Cow::Owned(stripped),
+        None => Cow::Borrowed(data),
+    }
+}
+
+fn strip_jpeg_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    let mut stripped = data[..2].to_vec();
+    let mut pos = 2;
+
+    loop {
+        if *data.get(pos)? != 0xff {
+            return None;
+        }
+
+        let marker = *data.get(pos + 1)?;
+
+        // The entropy-coded image data follows the start of scan marker
+        if marker == 0xda {
+            stripped.extend_from_slice(&data[pos..]);
+
+            return Some(stripped);
         }
 
-        unimplemented!()
+        let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+        let segment = data.get(pos..pos + 2 + length)?;
+
+        // APP1 (EXIF, XMP), APP13 (IPTC) and comments
+        if !matches!(marker, 0xe1 | 0xed | 0xfe) {
+            stripped.extend_from_slice(segment);
+        }
+
+        pos += segment.len();
     }
 }
+
+fn strip_png_metadata(data: &[u8], signature_length: usize) -> Option<Vec<u8>> {
+    let mut stripped = data[..signature_length].to_vec();
+    let mut pos = signature_length;
+
+    while pos < data.len() {
+        let length = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let chunk = data.get(pos..pos + 12 + length)?;
+
+        if !matches!(
+            &chunk[4..8],
+            b"tEXt" | b"zTXt" | b"iTXt" | b"eXIf" | b"tIME"
+        ) {
+            stripped.extend_from_slice(chunk);
+        }
+
+        pos += chunk.len();
+    }
+
+    Some(stripped)
+}
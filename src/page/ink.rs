@@ -1,5 +1,8 @@
-use crate::page::Renderer;
+use crate::cli::InkMode;
+use crate::page::{inkml, Renderer};
 use crate::utils::{px, AttributeSet, StyleSet};
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
 use itertools::Itertools;
 use onenote_parser::contents::{Ink, InkBoundingBox, InkPoint, InkStroke};
 
@@ -11,9 +14,9 @@ impl<'a> Renderer<'a> {
         ink: &Ink,
         display_bounding_box: Option<&InkBoundingBox>,
         embedded: bool,
-    ) -> String {
-        if ink.ink_strokes().is_empty() {
-            return String::new();
+    ) -> Result<String> {
+        if ink.ink_strokes().is_empty() || self.clean() {
+            return Ok(String::new());
         }
 
         let mut attrs = AttributeSet::new();
@@ -102,6 +105,14 @@ impl<'a> Renderer<'a> {
             attrs.set("style", styles.to_string());
         }
 
+        let svg = format!("<svg {}>{}</svg>", attrs.to_string(), path);
+
+        let svg = if self.section.opt.ink == InkMode::InkMl {
+            self.render_inkml_link(ink.ink_strokes(), svg)?
+        } else {
+            svg
+        };
+
         if embedded {
             let mut span_styles = StyleSet::new();
 
@@ -110,17 +121,35 @@ impl<'a> Renderer<'a> {
                 span_styles.set("height", px(bb.height() / Self::SVG_SCALING_FACTOR / 48.0));
             }
 
-            format!(
-                "<span style=\"{}\" class=\"ink-text\"><svg {}>{}</svg></span>",
+            Ok(format!(
+                "<span style=\"{}\" class=\"ink-text\">{}</span>",
                 span_styles.to_string(),
-                attrs.to_string(),
-                path
-            )
+                svg
+            ))
         } else {
-            format!("<svg {}>{}</svg>", attrs.to_string(), path)
+            Ok(svg)
         }
     }
 
+    /// Write the strokes to an InkML file and link the rendered drawing to it.
+    fn render_inkml_link(&mut self, strokes: &[InkStroke], svg: String) -> Result<String> {
+        let data = inkml::inkml(strokes);
+
+        let filename = if self.section.opt.hash_assets {
+            self.hashed_filename("ink.inkml", data.as_bytes())
+        } else {
+            self.determine_filename("ink.inkml")?
+        };
+        self.write_asset(&filename, data.as_bytes())
+            .wrap_err("Failed to write InkML file")?;
+
+        Ok(format!(
+            "<a class=\"ink-file\" href=\"{}\">{}</a>",
+            self.asset_url(&filename),
+            svg
+        ))
+    }
+
     fn render_ink_path(&mut self, strokes: &[InkStroke]) -> String {
         let mut attrs = AttributeSet::new();
 
@@ -138,15 +167,7 @@ impl<'a> Renderer<'a> {
         let opacity = (255 - stroke.transparency().unwrap_or_default()) as f32 / 256.0;
         attrs.set("opacity", format!("{:.2}", opacity));
 
-        let color = if let Some(value) = stroke.color() {
-            let r = value % 256;
-
-            let rem = (value - r) / 256;
-            let g = rem % 256;
-
-            let rem = (rem - g) / 256;
-            let b = rem % 256;
-
+        let color = if let Some((r, g, b)) = stroke_rgb(stroke) {
             format!("rgb({}, {}, {})", r, g, b)
         } else {
             "WindowText".to_string()
@@ -182,6 +203,21 @@ impl<'a> Renderer<'a> {
     }
 }
 
+/// The red, green and blue components of a stroke's color.
+pub(crate) fn stroke_rgb(stroke: &InkStroke) -> Option<(u32, u32, u32)> {
+    let value = stroke.color()?;
+
+    let r = value % 256;
+
+    let rem = (value - r) / 256;
+    let g = rem % 256;
+
+    let rem = (rem - g) / 256;
+    let b = rem % 256;
+
+    Some((r, g, b))
+}
+
 fn get_boundary<F: Fn(&InkPoint) -> f32>(strokes: &[InkStroke], coord: F) -> (f32, f32) {
     let mut min = f32::INFINITY;
     let mut max = f32::NEG_INFINITY;
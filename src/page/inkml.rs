@@ -0,0 +1,59 @@
+use crate::page::ink::stroke_rgb;
+use onenote_parser::contents::InkStroke;
+
+/// Serialize ink strokes as an InkML document. Coordinates are in
+/// himetric units (0.01 mm), as stored by OneNote.
+pub(crate) fn inkml(strokes: &[InkStroke]) -> String {
+    let mut brushes = String::new();
+    let mut traces = String::new();
+
+    for (i, stroke) in strokes.iter().enumerate() {
+        brushes.push_str(&format!(
+            "    <brush xml:id=\"br{}\">\n      <brushProperty name=\"width\" value=\"{}\" units=\"himetric\"/>\n",
+            i,
+            stroke.width().round()
+        ));
+
+        if let Some((r, g, b)) = stroke_rgb(stroke) {
+            brushes.push_str(&format!(
+                "      <brushProperty name=\"color\" value=\"#{:02X}{:02X}{:02X}\"/>\n",
+                r, g, b
+            ));
+        }
+
+        brushes.push_str("    </brush>\n");
+
+        traces.push_str(&format!(
+            "  <trace brushRef=\"#br{}\">{}</trace>\n",
+            i,
+            trace_points(stroke)
+        ));
+    }
+
+    let mut document = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    document.push_str("<ink xmlns=\"http://www.w3.org/2003/InkML\">\n");
+    document.push_str(&format!("  <definitions>\n{}  </definitions>\n", brushes));
+    document.push_str(&traces);
+    document.push_str("</ink>\n");
+
+    document
+}
+
+/// The absolute coordinates of a stroke's points. OneNote stores the first
+/// point absolute and the following ones relative to their predecessor.
+fn trace_points(stroke: &InkStroke) -> String {
+    let mut x = 0.0;
+    let mut y = 0.0;
+
+    stroke
+        .path()
+        .iter()
+        .map(|point| {
+            x += point.x();
+            y += point.y();
+
+            format!("{} {}", x.round(), y.round())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,92 @@
+use crate::utils::json_string;
+use itertools::Itertools;
+use onenote_parser::contents::{Content, NoteTag, OutlineElement, OutlineItem};
+use onenote_parser::page::{Page, PageContent};
+
+/// Build a schema.org `Article` JSON-LD object describing the page.
+pub(crate) fn page_json_ld(page: &Page, title: &str, include_author: bool) -> String {
+    let mut fields = vec![
+        ("@context", json_string("https://schema.org")),
+        ("@type", json_string("Article")),
+        ("headline", json_string(title)),
+    ];
+
+    if let Some(author) = page.author().filter(|_| include_author) {
+        fields.push((
+            "author",
+            format!("{{\"@type\":\"Person\",\"name\":{}}}", json_string(author)),
+        ));
+    }
+
+    let labels = page_note_tag_labels(page);
+
+    if !labels.is_empty() {
+        fields.push(("keywords", json_string(&labels.join(", "))));
+    }
+
+    format!(
+        "{{{}}}",
+        fields
+            .iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), value))
+            .join(",")
+    )
+}
+
+/// Collect the labels of all note tags used on the page, in order of
+/// appearance.
+pub(crate) fn page_note_tag_labels(page: &Page) -> Vec<String> {
+    let mut labels = Vec::new();
+
+    for content in page.contents() {
+        match content {
+            PageContent::Outline(outline) => outline_items_labels(outline.items(), &mut labels),
+            PageContent::Image(image) => note_tag_labels(image.note_tags(), &mut labels),
+            PageContent::EmbeddedFile(file) => note_tag_labels(file.note_tags(), &mut labels),
+            PageContent::Ink(_) | PageContent::Unknown => {}
+        }
+    }
+
+    labels
+}
+
+fn outline_items_labels(items: &[OutlineItem], labels: &mut Vec<String>) {
+    for item in items {
+        match item {
+            OutlineItem::Element(element) => outline_element_labels(element, labels),
+            OutlineItem::Group(group) => outline_items_labels(group.outlines(), labels),
+        }
+    }
+}
+
+fn outline_element_labels(element: &OutlineElement, labels: &mut Vec<String>) {
+    for content in element.contents() {
+        match content {
+            Content::RichText(text) => note_tag_labels(text.note_tags(), labels),
+            Content::Image(image) => note_tag_labels(image.note_tags(), labels),
+            Content::EmbeddedFile(file) => note_tag_labels(file.note_tags(), labels),
+            Content::Table(table) => {
+                note_tag_labels(table.note_tags(), labels);
+
+                for cell in table.contents().iter().flat_map(|row| row.contents()) {
+                    for element in cell.contents() {
+                        outline_element_labels(element, labels);
+                    }
+                }
+            }
+            Content::Ink(_) | Content::Unknown => {}
+        }
+    }
+
+    outline_items_labels(element.children(), labels);
+}
+
+fn note_tag_labels(note_tags: &[NoteTag], labels: &mut Vec<String>) {
+    for definition in note_tags.iter().filter_map(|tag| tag.definition()) {
+        let label = definition.label().to_string();
+
+        if !label.is_empty() && !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+}
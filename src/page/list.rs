@@ -1,7 +1,8 @@
 use crate::page::Renderer;
 use crate::utils::{px, AttributeSet, StyleSet};
 use color_eyre::Result;
-use onenote_parser::contents::{List, OutlineElement};
+use log::warn;
+use onenote_parser::contents::{Content, List, OutlineElement};
 use onenote_parser::property::common::ColorRef;
 
 const FORMAT_NUMBERED_LIST: char = '\u{fffd}';
@@ -15,8 +16,9 @@ impl<'a> Renderer<'a> {
         let mut contents = String::new();
         let mut in_list = false;
         let mut list_end = None;
+        let mut elements = elements.peekable();
 
-        for (element, parent_level, current_level) in elements {
+        while let Some((element, parent_level, current_level)) = elements.next() {
             if !in_list && self.is_list(element) {
                 let tags = self.list_tags(element);
                 let list_start = tags.0;
@@ -31,6 +33,33 @@ impl<'a> Renderer<'a> {
                 in_list = false;
             }
 
+            let caption = elements
+                .peek()
+                .copied()
+                .filter(|(next, _, _)| is_image_element(element) && is_caption_element(next));
+
+            if let Some((caption, caption_parent_level, caption_level)) = caption {
+                elements.next();
+
+                contents.push_str("<figure>");
+                contents.push_str(&self.render_outline_element(
+                    element,
+                    parent_level,
+                    current_level,
+                    indents,
+                )?);
+                contents.push_str("<figcaption>");
+                contents.push_str(&self.render_outline_element(
+                    caption,
+                    caption_parent_level,
+                    caption_level,
+                    indents,
+                )?);
+                contents.push_str("</figcaption></figure>");
+
+                continue;
+            }
+
             contents.push_str(&self.render_outline_element(
                 element,
                 parent_level,
@@ -76,22 +105,13 @@ impl<'a> Renderer<'a> {
 
         match list_format {
             [FORMAT_NUMBERED_LIST, '\u{0}', ..] => {}
-            [FORMAT_NUMBERED_LIST, '\u{1}', ..] => {
-                container_style.set("list-style-type", "upper-roman".to_string())
-            }
-            [FORMAT_NUMBERED_LIST, '\u{2}', ..] => {
-                container_style.set("list-style-type", "lower-roman".to_string())
-            }
-            [FORMAT_NUMBERED_LIST, '\u{3}', ..] => {
-                container_style.set("list-style-type", "upper-latin".to_string())
-            }
-            [FORMAT_NUMBERED_LIST, '\u{4}', ..] => {
-                container_style.set("list-style-type", "lower-latin".to_string())
-            }
-            [FORMAT_NUMBERED_LIST, c, ..] => {
-                dbg!(c);
-                unimplemented!();
-            }
+            [FORMAT_NUMBERED_LIST, format, ..] => match list_style_type(*format) {
+                Some(style) => container_style.set("list-style-type", style.to_string()),
+                None => warn!(
+                    "Unsupported list number format {:#x}, using decimal numbers",
+                    *format as u32
+                ),
+            },
             [c] => marker_style.set("content", format!("'{}'", c)),
             _ => {}
         }
@@ -123,9 +143,7 @@ impl<'a> Renderer<'a> {
             attrs.set("start", restart.to_string())
         }
 
-        if container_style.len() > 0 {
-            attrs.set("style", container_style.to_string());
-        }
+        self.set_styles(&mut attrs, container_style);
 
         let class = self.gen_class("list");
 
@@ -180,3 +198,49 @@ impl<'a> Renderer<'a> {
         element.list_contents().first().is_some()
     }
 }
+
+/// The CSS list style for a number format (see `MSONFC` in [MS-DOC]).
+///
+/// [MS-DOC]: https://docs.microsoft.com/en-us/openspecs/office_file_formats/ms-doc/
+fn list_style_type(format: char) -> Option<&'static str> {
+    let style = match format {
+        '\u{1}' => "upper-roman",
+        '\u{2}' => "lower-roman",
+        '\u{3}' => "upper-latin",
+        '\u{4}' => "lower-latin",
+        '\u{a}' => "japanese-informal",
+        '\u{b}' => "japanese-formal",
+        '\u{c}' | '\u{14}' => "katakana",
+        '\u{d}' | '\u{15}' => "katakana-iroha",
+        '\u{e}' | '\u{13}' => "decimal",
+        '\u{10}' => "cjk-decimal",
+        '\u{16}' => "decimal-leading-zero",
+        '\u{18}' => "hangul",
+        '\u{19}' => "hangul-consonant",
+        '\u{1e}' => "cjk-heavenly-stem",
+        '\u{1f}' => "cjk-earthly-branch",
+        '\u{21}' => "trad-chinese-informal",
+        '\u{22}' => "trad-chinese-formal",
+        '\u{25}' => "simp-chinese-informal",
+        '\u{26}' => "simp-chinese-formal",
+        '\u{2d}' | '\u{2f}' => "hebrew",
+        _ => return None,
+    };
+
+    Some(style)
+}
+
+/// Whether the outline element consists of a single image.
+fn is_image_element(element: &OutlineElement) -> bool {
+    element.list_contents().is_empty()
+        && element.children().is_empty()
+        && matches!(element.contents(), [Content::Image(_)])
+}
+
+/// Whether the outline element is a paragraph in the citation style, which is
+/// used to caption the image before it.
+fn is_caption_element(element: &OutlineElement) -> bool {
+    element.list_contents().is_empty()
+        && matches!(element.contents(), [Content::RichText(text)]
+            if text.paragraph_style().style_id() == Some("cite"))
+}
@@ -1,44 +1,162 @@
+use crate::cli::OutputFormat;
+use crate::export;
+use crate::export::PageInfo;
+use crate::notebook::css_color;
 use crate::section;
-use crate::utils::StyleSet;
+use crate::strings::strings;
+use crate::templates::html_escape;
+use crate::templates::merged;
+use crate::templates::page::PageMeta;
+use crate::utils::{url_path, AttributeSet, StyleSet};
 use color_eyre::Result;
+use log::warn;
 use onenote_parser::page::{Page, PageContent};
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
+pub(crate) mod asset;
+pub(crate) mod asset_writer;
 pub(crate) mod content;
+pub(crate) mod email;
 pub(crate) mod embedded_file;
+pub(crate) mod footer;
 pub(crate) mod image;
 pub(crate) mod ink;
+pub(crate) mod inkml;
+pub(crate) mod json_ld;
 pub(crate) mod list;
 pub(crate) mod note_tag;
+pub(crate) mod note_tag_map;
 pub(crate) mod outline;
+pub(crate) mod post_process;
+pub(crate) mod redaction;
+pub(crate) mod replacement;
 pub(crate) mod rich_text;
+#[cfg(feature = "scripting")]
+pub(crate) mod script;
+#[cfg(feature = "snapshots")]
+mod snapshot;
+pub(crate) mod split;
 pub(crate) mod table;
+pub(crate) mod text;
+pub(crate) mod text_rule;
+pub(crate) mod utility_classes;
+
+/// The maximum width and height of page thumbnails in pixels.
+const THUMBNAIL_SIZE: u32 = 160;
+
+/// The name of the thumbnail file of the page written to `file_name`.
+pub(crate) fn thumbnail_file_name(file_name: &str) -> String {
+    format!("{}.thumb.png", file_name)
+}
+
+/// Apply the replacement rules for the section named `section` and the
+/// redaction rules to a text that's written besides the page content, e.g. a
+/// page title in an index or the plain text of a page.
+pub(crate) fn with_text_rules(text: &str, section: &str) -> String {
+    let text = replacement::replace(text, section);
+
+    redaction::redact_uncounted(&text).into_owned()
+}
+
+/// The title of `page` with the text rules applied.
+fn title_with_text_rules(page: &Page, section: &str) -> String {
+    with_text_rules(
+        page.title_text().unwrap_or(&strings().untitled_page),
+        section,
+    )
+}
 
 pub(crate) struct Renderer<'a> {
+    /// The directory the page's images and attachments are written to.
     output: PathBuf,
+    /// The directory the page's files are written to.
+    page_dir: PathBuf,
+    /// The DokuWiki namespace of the page.
+    namespace: String,
+    /// The path of the page's section relative to the output directory.
+    section_path: String,
     section: &'a mut section::Renderer,
+    asset_prefix: String,
 
     in_list: bool,
-    global_styles: HashMap<String, StyleSet>,
+    global_styles: BTreeMap<String, StyleSet>,
     global_classes: HashSet<String>,
+    style_classes: HashMap<String, String>,
+
+    asset_time: Duration,
+
+    /// The URL of the attached file that precedes the current content, which
+    /// file printouts link to.
+    printout_source: Option<String>,
+
+    /// The labels of the note tags used on the page.
+    note_tag_labels: BTreeSet<String>,
+
+    /// The anchors used in the document, for the outline elements' IDs.
+    anchors: merged::Anchors,
+
+    /// The path of the page's thumbnail, until it has been written.
+    thumbnail: Option<PathBuf>,
+
+    /// The number of redactions made on the page.
+    redactions: usize,
 }
 
 impl<'a> Renderer<'a> {
-    pub(crate) fn new(output: PathBuf, section: &'a mut section::Renderer) -> Self {
+    pub(crate) fn new(section_dir: PathBuf, section: &'a mut section::Renderer) -> Self {
         Self {
-            output,
+            output: export::asset_dir(&section.opt, &section_dir),
+            page_dir: export::page_dir(&section.opt, &section_dir),
+            namespace: export::dokuwiki::namespace(&section.opt, &section_dir),
+            section_path: export::section_path(&section.opt, &section_dir),
             section,
+            asset_prefix: String::new(),
             in_list: false,
-            global_styles: HashMap::new(),
+            global_styles: BTreeMap::new(),
             global_classes: HashSet::new(),
+            style_classes: HashMap::new(),
+            asset_time: Duration::default(),
+            printout_source: None,
+            note_tag_labels: BTreeSet::new(),
+            anchors: merged::Anchors::default(),
+            thumbnail: None,
+            redactions: 0,
         }
     }
 
-    pub(crate) fn render_page(&mut self, page: &Page) -> Result<String> {
-        let title_text = page.title_text().unwrap_or("Untitled Page");
+    /// Render a page to one or more files in the output directory. Each file
+    /// is written as soon as it has been rendered.
+    pub(crate) fn render_page(
+        &mut self,
+        page: &Page,
+        conflicts: &[&Page],
+        file_name: &str,
+    ) -> Result<()> {
+        let title_text = title_with_text_rules(page, &self.section.name);
+        let title_text = title_text.as_str();
 
-        let mut content = String::new();
+        fs::create_dir_all(&self.page_dir)?;
+        fs::create_dir_all(&self.output)?;
+
+        if self.section.opt.thumbnails {
+            let thumbnail = self.output.join(thumbnail_file_name(file_name));
+
+            if thumbnail.is_file() {
+                fs::remove_file(&thumbnail)?;
+            }
+
+            self.thumbnail = Some(thumbnail);
+        }
+
+        let words = text::word_count(&text::page_text(page));
+        let minutes = text::reading_time(words);
+        self.section.record_word_count(file_name, words, minutes);
+
+        let mut title_field = String::new();
 
         if let Some(title) = page.title() {
             let mut styles = StyleSet::new();
@@ -52,26 +170,277 @@ impl<'a> Renderer<'a> {
                 format!("{}px", (title.offset_horizontal() * 48.0 + 48.0).round()),
             );
 
-            let mut title_field = format!("<div class=\"title\" style=\"{}\">", styles.to_string());
+            let mut attrs = AttributeSet::new();
+            attrs.set("class", "title".to_string());
+            self.set_styles(&mut attrs, styles);
+
+            if let Some(author) = page
+                .author()
+                .filter(|_| self.section.opt.show_authors && !self.section.opt.strip_metadata)
+            {
+                let author = html_escape(author)?;
+
+                attrs.set("title", author.clone());
+                attrs.set("data-author", author);
+            }
+
+            title_field.push_str(&format!("<div {}>", attrs));
 
             for outline in title.contents() {
                 title_field.push_str(&self.render_outline(outline)?)
             }
 
+            if self.section.opt.reading_time {
+                title_field.push_str(&format!(
+                    "<div class=\"reading-time\">{}</div>",
+                    strings()
+                        .reading_time
+                        .replace("{words}", &words.to_string())
+                        .replace("{minutes}", &minutes.to_string())
+                ));
+            }
+
             title_field.push_str("</div>");
+        }
+
+        let mut parts = match self.section.opt.split_by {
+            Some(mode) => self.render_split_contents(page.contents(), mode)?,
+            None => vec![page
+                .contents()
+                .iter()
+                .map(|content| self.render_page_content(content))
+                .collect::<Result<String>>()?],
+        };
+
+        if let Some(last) = parts.last_mut() {
+            last.push_str(&self.render_conflicts(conflicts)?);
+        }
 
-            content.push_str(&title_field);
+        let format = self.section.opt.format;
+
+        let mut file_names = vec![export::page_file_name(format, file_name)];
+
+        for i in 1..parts.len() {
+            let part_name =
+                self.section
+                    .determine_page_filename(&format!("{} ({})", file_name, i + 1))?;
+
+            file_names.push(export::page_file_name(format, &part_name));
+        }
+
+        let color = self.section.color.as_ref().map(css_color);
+        let json_ld = if self.section.opt.json_ld {
+            Some(json_ld::page_json_ld(
+                page,
+                title_text,
+                !self.section.opt.strip_metadata,
+            ))
+        } else {
+            None
+        };
+
+        let onenote_link = self.section.onenote_link(title_text);
+        let section_name = self
+            .page_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        for (i, part) in parts.into_iter().enumerate() {
+            if format != OutputFormat::Html {
+                let title = match i {
+                    0 => title_text.to_string(),
+                    i => format!("{} ({})", title_text, i + 1),
+                };
+                let info = PageInfo {
+                    title: &title,
+                    section: &section_name,
+                    tags: self.note_tag_labels.iter().cloned().collect(),
+                    namespace: self.namespace.clone(),
+                    path: self.section_path.clone(),
+                };
+
+                self.write_page_file(&file_names[i], &title, export::render(format, &info, &part))?;
+
+                continue;
+            }
+
+            let content = if i == 0 {
+                title_field.clone() + &part
+            } else {
+                part
+            };
+
+            let meta = PageMeta {
+                previous_part: i.checked_sub(1).and_then(|i| file_names.get(i)),
+                next_part: file_names.get(i + 1),
+                url: self.page_url(&file_names[i]),
+                json_ld: json_ld.clone(),
+                onenote_link: onenote_link.clone(),
+                outline_controls: self.section.opt.outline_controls,
+                conflicts: !conflicts.is_empty() && i + 1 == file_names.len(),
+                word_count: Some(words),
+                reading_time: Some(minutes),
+                favicon_dir: Some(String::new()).filter(|_| self.section.opt.favicon),
+                footer: footer::render(
+                    self.section.notebook.as_deref(),
+                    &self.section.name,
+                    title_text,
+                ),
+            };
+
+            let html = if self.section.opt.fragment {
+                crate::templates::page::render_fragment(&content, &self.global_styles)?
+            } else {
+                crate::templates::page::render(
+                    title_text,
+                    &content,
+                    &self.global_styles,
+                    color.clone(),
+                    meta,
+                )?
+            };
+
+            self.write_page_file(&file_names[i], title_text, html)?;
+        }
+
+        if self.redactions > 0 {
+            self.section.record_redactions(file_name, self.redactions);
         }
 
-        let page_content = page
+        Ok(())
+    }
+
+    /// Write a rendered page to `file_name` in the page directory, after
+    /// passing it to the script and the post-processing command if set.
+    /// Commands that read the written file run once the page's assets have
+    /// been written.
+    #[cfg_attr(not(feature = "scripting"), allow(unused_variables))]
+    fn write_page_file(&mut self, file_name: &str, title: &str, content: String) -> Result<()> {
+        #[cfg(feature = "scripting")]
+        let content = script::transform(
+            content,
+            &script::ScriptPage {
+                title,
+                section: &self.section.name,
+                file_name,
+                extension: self.section.opt.format.extension(),
+            },
+        )?;
+
+        let path = self.page_dir.join(file_name);
+
+        match self.section.opt.post_process.clone() {
+            Some(command) if post_process::runs_on_file(&command) => {
+                fs::write(&path, content)?;
+                self.section.flush_assets()?;
+                post_process::run_on_file(&command, &path)?;
+            }
+            Some(command) => fs::write(&path, post_process::run_on_content(&command, content)?)?,
+            None => fs::write(&path, content)?,
+        }
+
+        #[cfg(feature = "snapshots")]
+        if let Some(format) = self.section.opt.snapshot {
+            if self.section.opt.format == OutputFormat::Html {
+                self.section.flush_assets()?;
+                snapshot::render(&self.section.opt.chromium, format, &path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the contents of conflict pages to append them to the page they
+    /// conflict with.
+    fn render_conflicts(&mut self, conflicts: &[&Page]) -> Result<String> {
+        let mut html = String::new();
+
+        for page in conflicts {
+            let title = title_with_text_rules(page, &self.section.name);
+
+            html.push_str(&format!(
+                "<section class=\"conflict-page\">\n<h2 class=\"conflict-heading\">{}</h2>\n",
+                html_escape(&title)?
+            ));
+
+            for content in page.contents() {
+                html.push_str(&self.render_page_content(content)?);
+            }
+
+            html.push_str("</section>\n");
+        }
+
+        Ok(html)
+    }
+
+    /// Render the contents of a page without its title, to be included in a
+    /// merged document. The page's styles are scoped to the `scope` selector.
+    pub(crate) fn render_page_fragment(
+        &mut self,
+        page: &Page,
+        conflicts: &[&Page],
+        scope: &str,
+    ) -> Result<(String, Vec<(String, StyleSet)>)> {
+        let content = page
             .contents()
             .iter()
             .map(|content| self.render_page_content(content))
-            .collect::<Result<String>>()?;
+            .collect::<Result<String>>()?
+            + &self.render_conflicts(conflicts)?;
+
+        let styles = std::mem::take(&mut self.global_styles)
+            .into_iter()
+            .map(|(selector, styles)| (format!("{} {}", scope, selector), styles))
+            .collect();
+
+        Ok((content, styles))
+    }
+
+    /// Set the path of the directory containing the page's files, relative to
+    /// the rendered HTML file.
+    pub(crate) fn set_asset_prefix(&mut self, prefix: String) {
+        self.asset_prefix = prefix;
+    }
 
-        content.push_str(&page_content);
+    /// Number the outline elements' IDs with the `anchors` already used in
+    /// a merged document.
+    pub(crate) fn set_anchors(&mut self, anchors: merged::Anchors) {
+        self.anchors = anchors;
+    }
 
-        crate::templates::page::render(title_text, &content, &self.global_styles)
+    /// The anchors used so far, including the page's outline elements.
+    pub(crate) fn take_anchors(&mut self) -> merged::Anchors {
+        std::mem::take(&mut self.anchors)
+    }
+
+    pub(crate) fn asset_url(&self, filename: &str) -> String {
+        let opt = &self.section.opt;
+
+        if let Some((url_prefix, output_root)) =
+            opt.asset_url_prefix.as_ref().zip(opt.output.as_ref())
+        {
+            if let Ok(path) = self.output.join(filename).strip_prefix(output_root) {
+                return url_prefix.to_string() + &url_path(path);
+            }
+        }
+
+        format!("{}{}", self.asset_prefix, filename)
+    }
+
+    /// The time spent writing the page's assets so far.
+    pub(crate) fn asset_time(&self) -> Duration {
+        self.asset_time
+    }
+
+    /// The absolute URL of a page file, if a base URL is given.
+    fn page_url(&self, file_name: &str) -> Option<String> {
+        let base_url = self.section.opt.base_url.as_ref()?;
+        let output_root = self.section.opt.output.as_ref()?;
+        let path = self.page_dir.join(file_name);
+
+        Some(base_url.to_string() + &url_path(path.strip_prefix(output_root).ok()?))
     }
 
     pub(crate) fn gen_class(&mut self, prefix: &str) -> String {
@@ -89,13 +458,123 @@ impl<'a> Renderer<'a> {
         }
     }
 
+    /// Get the classes for the styles: framework utility classes (if enabled)
+    /// and a class that's shared by all elements with the same remaining
+    /// styles, instead of repeating them inline. Returns `None` if there are
+    /// no styles.
+    pub(crate) fn style_class(&mut self, mut styles: StyleSet) -> Option<String> {
+        if self.clean() {
+            return None;
+        }
+
+        let mut classes = match self.section.opt.utility_classes {
+            Some(framework) => utility_classes::extract(framework, &mut styles),
+            None => Vec::new(),
+        };
+
+        if styles.len() > 0 {
+            classes.push(self.shared_style_class(styles));
+        }
+
+        if classes.is_empty() {
+            None
+        } else {
+            Some(classes.join(" "))
+        }
+    }
+
+    fn shared_style_class(&mut self, styles: StyleSet) -> String {
+        let key = styles.to_string();
+
+        if let Some(class) = self.style_classes.get(&key) {
+            return class.clone();
+        }
+
+        let class = self.gen_class("style");
+
+        self.global_styles.insert(format!(".{}", class), styles);
+        self.style_classes.insert(key, class.clone());
+
+        class
+    }
+
+    /// Whether to only render the structure and emphasis of the page, which
+    /// is the case for `--clean` and all output formats other than HTML.
+    pub(crate) fn clean(&self) -> bool {
+        self.section.opt.clean || self.section.opt.format != OutputFormat::Html
+    }
+
+    /// Set the inline styles of an element, unless clean output is enabled.
+    pub(crate) fn set_styles(&self, attrs: &mut AttributeSet, styles: StyleSet) {
+        if styles.len() > 0 && !self.clean() {
+            attrs.set("style", styles.to_string());
+        }
+    }
+
+    /// Annotate an element with the type of OneNote content it represents
+    /// and its position among the elements of that type, if enabled. The
+    /// parser doesn't expose OneNote's object IDs, so the index isn't stable
+    /// across edits of the page.
+    pub(crate) fn set_data_attributes(
+        &mut self,
+        attrs: &mut AttributeSet,
+        content_type: &'static str,
+    ) {
+        if !self.section.opt.data_attributes {
+            return;
+        }
+
+        attrs.set("data-onenote-type", content_type.to_string());
+        attrs.set("data-onenote-index", self.gen_class(content_type));
+    }
+
+    /// Apply the replacement and redaction rules to a text of the page.
+    /// Redactions are counted for the page.
+    pub(crate) fn apply_text_rules(&mut self, text: String) -> String {
+        let text = match replacement::replace(&text, &self.section.name) {
+            Cow::Owned(replaced) => replaced,
+            Cow::Borrowed(_) => text,
+        };
+        let (redacted, count) = redaction::redact(&text);
+
+        if count == 0 {
+            return text;
+        }
+
+        self.redactions += count;
+
+        redacted.into_owned()
+    }
+
+    /// Write a thumbnail of the page's first image.
+    pub(crate) fn write_thumbnail(&mut self, data: &[u8]) {
+        let path = match self.thumbnail.take() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let result = image::load_from_memory(data)
+            .and_then(|image| image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).save(&path));
+
+        if let Err(err) = result {
+            warn!("Failed to write thumbnail {}: {}", path.display(), err);
+        }
+    }
+
     fn render_page_content(&mut self, content: &PageContent) -> Result<String> {
         match content {
             PageContent::Outline(outline) => self.render_outline(outline),
             PageContent::Image(image) => self.render_image(image),
+            PageContent::EmbeddedFile(file) if self.section.opt.exact_layout => {
+                self.render_positioned_embedded_file(file)
+            }
             PageContent::EmbeddedFile(file) => self.render_embedded_file(file),
-            PageContent::Ink(ink) => Ok(self.render_ink(ink, None, false)),
-            PageContent::Unknown => Ok(String::new()),
+            PageContent::Ink(ink) => self.render_ink(ink, None, false),
+            PageContent::Unknown => {
+                self.printout_source = None;
+
+                Ok(String::new())
+            }
         }
     }
 }
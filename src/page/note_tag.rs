@@ -1,5 +1,7 @@
+use crate::page::note_tag_map;
 use crate::page::Renderer;
-use crate::utils::StyleSet;
+use crate::utils::{css_var_color, highlight_color, slugify, AttributeSet, StyleSet};
+use itertools::Itertools;
 use onenote_parser::contents::{NoteTag, OutlineElement};
 use onenote_parser::property::common::ColorRef;
 use onenote_parser::property::note_tag::{ActionItemStatus, NoteTagShape};
@@ -13,30 +15,66 @@ const COLOR_RED: &str = "#db5b4d";
 const COLOR_YELLOW: &str = "#ffd678";
 
 const ICON_ARROW_RIGHT: &str = include_str!("../../assets/icons/arrow-right-line.svg");
+const ICON_ARROW_SOLID: &str = include_str!("../../assets/icons/arrow-right-fill.svg");
 const ICON_AWARD: &str = include_str!("../../assets/icons/award-line.svg");
+const ICON_BELL: &str = include_str!("../../assets/icons/notification-3-line.svg");
+const ICON_BINOCULARS: &str = include_str!("../../assets/icons/binoculars-fill.svg");
 const ICON_BOOK: &str = include_str!("../../assets/icons/book-open-line.svg");
 const ICON_BUBBLE: &str = include_str!("../../assets/icons/chat-4-line.svg");
+const ICON_CALENDAR: &str = include_str!("../../assets/icons/calendar-line.svg");
+const ICON_CAR: &str = include_str!("../../assets/icons/material-directions-car.svg");
 const ICON_CHECKBOX_COMPLETE: &str = include_str!("../../assets/icons/checkbox-fill.svg");
 const ICON_CHECKBOX_EMPTY: &str = include_str!("../../assets/icons/checkbox-blank-line.svg");
 const ICON_CHECK_MARK: &str = include_str!("../../assets/icons/check-line.svg");
 const ICON_CIRCLE: &str = include_str!("../../assets/icons/checkbox-blank-circle-fill.svg");
+const ICON_CLOCK: &str = include_str!("../../assets/icons/time-line.svg");
+const ICON_CLOUD: &str = include_str!("../../assets/icons/cloud-fill.svg");
+const ICON_COINS: &str = include_str!("../../assets/icons/money-dollar-circle-line.svg");
 const ICON_CONTACT: &str = include_str!("../../assets/icons/contacts-line.svg");
+const ICON_DOLLAR: &str = include_str!("../../assets/icons/money-dollar-line.svg");
+const ICON_EIGHT_POINT_STAR: &str = include_str!("../../assets/icons/star-eight-fill.svg");
 const ICON_EMAIL: &str = include_str!("../../assets/icons/send-plane-2-line.svg");
+const ICON_ENVELOPE_CLOSED: &str = include_str!("../../assets/icons/mail-fill.svg");
+const ICON_ENVELOPE_OPEN: &str = include_str!("../../assets/icons/mail-open-fill.svg");
 const ICON_ERROR: &str = include_str!("../../assets/icons/error-warning-line.svg");
 const ICON_FILM: &str = include_str!("../../assets/icons/film-line.svg");
 const ICON_FLAG: &str = include_str!("../../assets/icons/flag-fill.svg");
+const ICON_FLOWER: &str = include_str!("../../assets/icons/flower-fill.svg");
+const ICON_FROWNING_FACE: &str = include_str!("../../assets/icons/emotion-unhappy-fill.svg");
+const ICON_GLOBE: &str = include_str!("../../assets/icons/global-line.svg");
+const ICON_HEART: &str = include_str!("../../assets/icons/heart-fill.svg");
 const ICON_HOME: &str = include_str!("../../assets/icons/home-4-line.svg");
+const ICON_KEY: &str = include_str!("../../assets/icons/key-2-line.svg");
+const ICON_LAPTOP: &str = include_str!("../../assets/icons/macbook-line.svg");
+const ICON_LIGHTNING_BOLT: &str = include_str!("../../assets/icons/flashlight-fill.svg");
 const ICON_LIGHT_BULB: &str = include_str!("../../assets/icons/lightbulb-line.svg");
 const ICON_LINK: &str = include_str!("../../assets/icons/link.svg");
 const ICON_LOCK: &str = include_str!("../../assets/icons/lock-line.svg");
+const ICON_MOBILE_PHONE: &str = include_str!("../../assets/icons/smartphone-line.svg");
 const ICON_MUSIC: &str = include_str!("../../assets/icons/music-fill.svg");
 const ICON_PAPER: &str = include_str!("../../assets/icons/file-list-2-line.svg");
+const ICON_PAPER_CLIP: &str = include_str!("../../assets/icons/attachment-2.svg");
 const ICON_PEN: &str = include_str!("../../assets/icons/mark-pen-line.svg");
 const ICON_PERSON: &str = include_str!("../../assets/icons/user-line.svg");
 const ICON_PHONE: &str = include_str!("../../assets/icons/phone-line.svg");
+const ICON_PLANE: &str = include_str!("../../assets/icons/material-flight.svg");
+const ICON_PRESENTATION: &str = include_str!("../../assets/icons/slideshow-line.svg");
+const ICON_PUSHPIN: &str = include_str!("../../assets/icons/pushpin-fill.svg");
+const ICON_QUESTION_BALLOON: &str = include_str!("../../assets/icons/questionnaire-line.svg");
 const ICON_QUESTION_MARK: &str = include_str!("../../assets/icons/question-mark.svg");
+const ICON_QUOTATION_MARK: &str = include_str!("../../assets/icons/double-quotes-l.svg");
+const ICON_RESEARCH: &str = include_str!("../../assets/icons/search-line.svg");
+const ICON_SMILING_FACE: &str = include_str!("../../assets/icons/emotion-happy-fill.svg");
 const ICON_SQUARE: &str = include_str!("../../assets/icons/checkbox-blank-fill.svg");
 const ICON_STAR: &str = include_str!("../../assets/icons/star-fill.svg");
+const ICON_SUN: &str = include_str!("../../assets/icons/drawn-sun.svg");
+const ICON_TARGET: &str = include_str!("../../assets/icons/focus-line.svg");
+const ICON_TARGET_SOLID: &str = include_str!("../../assets/icons/focus-fill.svg");
+const ICON_TRIANGLE: &str = include_str!("../../assets/icons/drawn-triangle.svg");
+const ICON_TWO_PEOPLE: &str = include_str!("../../assets/icons/group-line.svg");
+const ICON_UMBRELLA: &str = include_str!("../../assets/icons/umbrella-fill.svg");
+const ICON_X: &str = include_str!("../../assets/icons/close-line.svg");
+const ICON_X_WITH_DOTS: &str = include_str!("../../assets/icons/close-dots-line.svg");
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum IconSize {
@@ -52,7 +90,11 @@ impl<'a> Renderer<'a> {
     ) -> String {
         if let Some((markup, styles)) = self.render_note_tags(note_tags) {
             let mut contents = String::new();
-            contents.push_str(&format!("<div style=\"{}\">{}", styles, markup));
+            let mut attrs = AttributeSet::new();
+            self.set_styles(&mut attrs, styles);
+            set_note_tag_attributes(&mut attrs, note_tags);
+
+            contents.push_str(&format!("<div {}>{}", attrs, markup));
             contents.push_str(&content);
             contents.push_str("</div>");
 
@@ -72,17 +114,45 @@ impl<'a> Renderer<'a> {
 
         for note_tag in note_tags {
             if let Some(def) = note_tag.definition() {
+                self.note_tag_labels.insert(def.label().to_string());
+
                 if let Some(ColorRef::Manual { r, g, b }) = def.highlight_color() {
-                    styles.set("background-color", format!("rgb({},{},{})", r, g, b));
+                    styles.set("background-color", highlight_color(r, g, b));
                 }
 
                 if let Some(ColorRef::Manual { r, g, b }) = def.text_color() {
-                    styles.set("color", format!("rgb({},{},{})", r, g, b));
+                    styles.set("color", css_var_color("color", None, r, g, b));
+                }
+
+                let completed = note_tag.item_status().completed();
+
+                if completed {
+                    styles.set("text-decoration", "line-through".to_string());
                 }
 
-                if def.shape() != NoteTagShape::NoIcon {
-                    let (icon, icon_style) =
+                if let Some(html) = note_tag_map::get(def.label(), def.shape()) {
+                    markup.push_str(&html);
+                } else if self.clean() {
+                    if is_checkbox(def.shape()) {
+                        let checked = if note_tag.item_status().completed() {
+                            " checked"
+                        } else {
+                            ""
+                        };
+
+                        markup.push_str(&format!("<input type=\"checkbox\" disabled{}>", checked));
+                    }
+                } else if def.shape() != NoteTagShape::NoIcon {
+                    let (mut icon, icon_style) =
                         self.note_tag_icon(def.shape(), note_tag.item_status());
+
+                    // Checkboxes show their state, other tags get a check mark
+                    if completed && !is_checkbox(def.shape()) {
+                        icon.to_mut().push_str(&format!(
+                            "<span class=\"icon-secondary\">{}</span>",
+                            ICON_CHECK_MARK
+                        ));
+                    }
                     let mut icon_classes = vec!["note-tag-icon".to_string()];
 
                     if icon_style.len() > 0 {
@@ -160,12 +230,12 @@ impl<'a> Renderer<'a> {
                     self.icon_style(IconSize::Normal, style),
                 )
             }
-            NoteTagShape::BlueFollowUpFlag => unimplemented!(),
+            NoteTagShape::BlueFollowUpFlag => self.icon_colored(style, ICON_FLAG, COLOR_BLUE),
             NoteTagShape::QuestionMark => (
                 Cow::from(ICON_QUESTION_MARK),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::BlueRightArrow => unimplemented!(),
+            NoteTagShape::BlueRightArrow => self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_BLUE, 0),
             NoteTagShape::HighPriority => (
                 Cow::from(ICON_ERROR),
                 self.icon_style(IconSize::Normal, style),
@@ -174,13 +244,13 @@ impl<'a> Renderer<'a> {
                 Cow::from(ICON_PHONE),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::Meeting => unimplemented!(),
-            NoteTagShape::TimeSensitive => unimplemented!(),
+            NoteTagShape::Meeting => self.icon_plain(style, ICON_CALENDAR),
+            NoteTagShape::TimeSensitive => self.icon_plain(style, ICON_CLOCK),
             NoteTagShape::LightBulb => (
                 Cow::from(ICON_LIGHT_BULB),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::Pushpin => unimplemented!(),
+            NoteTagShape::Pushpin => self.icon_colored(style, ICON_PUSHPIN, COLOR_RED),
             NoteTagShape::Home => (
                 Cow::from(ICON_HOME),
                 self.icon_style(IconSize::Normal, style),
@@ -189,78 +259,110 @@ impl<'a> Renderer<'a> {
                 Cow::from(ICON_BUBBLE),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::SmilingFace => unimplemented!(),
+            NoteTagShape::SmilingFace => self.icon_colored(style, ICON_SMILING_FACE, COLOR_YELLOW),
             NoteTagShape::AwardRibbon => (
                 Cow::from(ICON_AWARD),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::YellowKey => unimplemented!(),
+            NoteTagShape::YellowKey => self.icon_colored(style, ICON_KEY, COLOR_YELLOW),
             NoteTagShape::BlueCheckBox1 => self.icon_checkbox_with_1(status, style, COLOR_BLUE),
-            NoteTagShape::BlueCircle1 => unimplemented!(),
+            NoteTagShape::BlueCircle1 => self.icon_numbered_circle(style, COLOR_BLUE, 1),
             NoteTagShape::BlueCheckBox2 => self.icon_checkbox_with_2(status, style, COLOR_BLUE),
-            NoteTagShape::BlueCircle2 => unimplemented!(),
+            NoteTagShape::BlueCircle2 => self.icon_numbered_circle(style, COLOR_BLUE, 2),
             NoteTagShape::BlueCheckBox3 => self.icon_checkbox_with_3(status, style, COLOR_BLUE),
-            NoteTagShape::BlueCircle3 => unimplemented!(),
-            NoteTagShape::BlueEightPointStar => unimplemented!(),
+            NoteTagShape::BlueCircle3 => self.icon_numbered_circle(style, COLOR_BLUE, 3),
+            NoteTagShape::BlueEightPointStar => {
+                self.icon_colored(style, ICON_EIGHT_POINT_STAR, COLOR_BLUE)
+            }
             NoteTagShape::BlueCheckMark => self.icon_checkmark(style, COLOR_BLUE),
             NoteTagShape::BlueCircle => self.icon_circle(style, COLOR_BLUE),
-            NoteTagShape::BlueDownArrow => unimplemented!(),
-            NoteTagShape::BlueLeftArrow => unimplemented!(),
-            NoteTagShape::BlueSolidTarget => unimplemented!(),
-            NoteTagShape::BlueStar => unimplemented!(),
-            NoteTagShape::BlueSun => unimplemented!(),
-            NoteTagShape::BlueTarget => unimplemented!(),
-            NoteTagShape::BlueTriangle => unimplemented!(),
-            NoteTagShape::BlueUmbrella => unimplemented!(),
-            NoteTagShape::BlueUpArrow => unimplemented!(),
-            NoteTagShape::BlueXWithDots => unimplemented!(),
-            NoteTagShape::BlueX => unimplemented!(),
+            NoteTagShape::BlueDownArrow => self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_BLUE, 90),
+            NoteTagShape::BlueLeftArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_BLUE, 180)
+            }
+            NoteTagShape::BlueSolidTarget => {
+                self.icon_colored(style, ICON_TARGET_SOLID, COLOR_BLUE)
+            }
+            NoteTagShape::BlueStar => self.icon_colored(style, ICON_STAR, COLOR_BLUE),
+            NoteTagShape::BlueSun => self.icon_colored(style, ICON_SUN, COLOR_BLUE),
+            NoteTagShape::BlueTarget => self.icon_colored(style, ICON_TARGET, COLOR_BLUE),
+            NoteTagShape::BlueTriangle => self.icon_colored(style, ICON_TRIANGLE, COLOR_BLUE),
+            NoteTagShape::BlueUmbrella => self.icon_colored(style, ICON_UMBRELLA, COLOR_BLUE),
+            NoteTagShape::BlueUpArrow => self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_BLUE, 270),
+            NoteTagShape::BlueXWithDots => self.icon_colored(style, ICON_X_WITH_DOTS, COLOR_BLUE),
+            NoteTagShape::BlueX => self.icon_colored(style, ICON_X, COLOR_BLUE),
             NoteTagShape::GreenCheckBox1 => self.icon_checkbox_with_1(status, style, COLOR_GREEN),
-            NoteTagShape::GreenCircle1 => unimplemented!(),
+            NoteTagShape::GreenCircle1 => self.icon_numbered_circle(style, COLOR_GREEN, 1),
             NoteTagShape::GreenCheckBox2 => self.icon_checkbox_with_2(status, style, COLOR_GREEN),
-            NoteTagShape::GreenCircle2 => unimplemented!(),
+            NoteTagShape::GreenCircle2 => self.icon_numbered_circle(style, COLOR_GREEN, 2),
             NoteTagShape::GreenCheckBox3 => self.icon_checkbox_with_3(status, style, COLOR_GREEN),
-            NoteTagShape::GreenCircle3 => unimplemented!(),
-            NoteTagShape::GreenEightPointStar => unimplemented!(),
+            NoteTagShape::GreenCircle3 => self.icon_numbered_circle(style, COLOR_GREEN, 3),
+            NoteTagShape::GreenEightPointStar => {
+                self.icon_colored(style, ICON_EIGHT_POINT_STAR, COLOR_GREEN)
+            }
             NoteTagShape::GreenCheckMark => self.icon_checkmark(style, COLOR_GREEN),
             NoteTagShape::GreenCircle => self.icon_circle(style, COLOR_GREEN),
-            NoteTagShape::GreenDownArrow => unimplemented!(),
-            NoteTagShape::GreenLeftArrow => unimplemented!(),
-            NoteTagShape::GreenRightArrow => unimplemented!(),
-            NoteTagShape::GreenSolidArrow => unimplemented!(),
-            NoteTagShape::GreenStar => unimplemented!(),
-            NoteTagShape::GreenSun => unimplemented!(),
-            NoteTagShape::GreenTarget => unimplemented!(),
-            NoteTagShape::GreenTriangle => unimplemented!(),
-            NoteTagShape::GreenUmbrella => unimplemented!(),
-            NoteTagShape::GreenUpArrow => unimplemented!(),
-            NoteTagShape::GreenXWithDots => unimplemented!(),
-            NoteTagShape::GreenX => unimplemented!(),
+            NoteTagShape::GreenDownArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_GREEN, 90)
+            }
+            NoteTagShape::GreenLeftArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_GREEN, 180)
+            }
+            NoteTagShape::GreenRightArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_GREEN, 0)
+            }
+            NoteTagShape::GreenSolidArrow => {
+                self.icon_arrow(style, ICON_ARROW_SOLID, COLOR_GREEN, 0)
+            }
+            NoteTagShape::GreenStar => self.icon_colored(style, ICON_STAR, COLOR_GREEN),
+            NoteTagShape::GreenSun => self.icon_colored(style, ICON_SUN, COLOR_GREEN),
+            NoteTagShape::GreenTarget => self.icon_colored(style, ICON_TARGET, COLOR_GREEN),
+            NoteTagShape::GreenTriangle => self.icon_colored(style, ICON_TRIANGLE, COLOR_GREEN),
+            NoteTagShape::GreenUmbrella => self.icon_colored(style, ICON_UMBRELLA, COLOR_GREEN),
+            NoteTagShape::GreenUpArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_GREEN, 270)
+            }
+            NoteTagShape::GreenXWithDots => self.icon_colored(style, ICON_X_WITH_DOTS, COLOR_GREEN),
+            NoteTagShape::GreenX => self.icon_colored(style, ICON_X, COLOR_GREEN),
             NoteTagShape::YellowCheckBox1 => self.icon_checkbox_with_1(status, style, COLOR_YELLOW),
-            NoteTagShape::YellowCircle1 => unimplemented!(),
+            NoteTagShape::YellowCircle1 => self.icon_numbered_circle(style, COLOR_YELLOW, 1),
             NoteTagShape::YellowCheckBox2 => self.icon_checkbox_with_2(status, style, COLOR_YELLOW),
-            NoteTagShape::YellowCircle2 => unimplemented!(),
+            NoteTagShape::YellowCircle2 => self.icon_numbered_circle(style, COLOR_YELLOW, 2),
             NoteTagShape::YellowCheckBox3 => self.icon_checkbox_with_3(status, style, COLOR_YELLOW),
-            NoteTagShape::YellowCircle3 => unimplemented!(),
-            NoteTagShape::YellowEightPointStar => unimplemented!(),
+            NoteTagShape::YellowCircle3 => self.icon_numbered_circle(style, COLOR_YELLOW, 3),
+            NoteTagShape::YellowEightPointStar => {
+                self.icon_colored(style, ICON_EIGHT_POINT_STAR, COLOR_YELLOW)
+            }
             NoteTagShape::YellowCheckMark => self.icon_checkmark(style, COLOR_YELLOW),
             NoteTagShape::YellowCircle => self.icon_circle(style, COLOR_YELLOW),
-            NoteTagShape::YellowDownArrow => unimplemented!(),
-            NoteTagShape::YellowLeftArrow => unimplemented!(),
-            NoteTagShape::YellowRightArrow => unimplemented!(),
-            NoteTagShape::YellowSolidTarget => unimplemented!(),
-            NoteTagShape::YellowSun => unimplemented!(),
-            NoteTagShape::YellowTarget => unimplemented!(),
-            NoteTagShape::YellowTriangle => unimplemented!(),
-            NoteTagShape::YellowUmbrella => unimplemented!(),
-            NoteTagShape::YellowUpArrow => unimplemented!(),
-            NoteTagShape::YellowXWithDots => unimplemented!(),
-            NoteTagShape::YellowX => unimplemented!(),
-            NoteTagShape::FollowUpTodayFlag => unimplemented!(),
-            NoteTagShape::FollowUpTomorrowFlag => unimplemented!(),
-            NoteTagShape::FollowUpThisWeekFlag => unimplemented!(),
-            NoteTagShape::FollowUpNextWeekFlag => unimplemented!(),
-            NoteTagShape::NoFollowUpDateFlag => unimplemented!(),
+            NoteTagShape::YellowDownArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_YELLOW, 90)
+            }
+            NoteTagShape::YellowLeftArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_YELLOW, 180)
+            }
+            NoteTagShape::YellowRightArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_YELLOW, 0)
+            }
+            NoteTagShape::YellowSolidTarget => {
+                self.icon_colored(style, ICON_TARGET_SOLID, COLOR_YELLOW)
+            }
+            NoteTagShape::YellowSun => self.icon_colored(style, ICON_SUN, COLOR_YELLOW),
+            NoteTagShape::YellowTarget => self.icon_colored(style, ICON_TARGET, COLOR_YELLOW),
+            NoteTagShape::YellowTriangle => self.icon_colored(style, ICON_TRIANGLE, COLOR_YELLOW),
+            NoteTagShape::YellowUmbrella => self.icon_colored(style, ICON_UMBRELLA, COLOR_YELLOW),
+            NoteTagShape::YellowUpArrow => {
+                self.icon_arrow(style, ICON_ARROW_RIGHT, COLOR_YELLOW, 270)
+            }
+            NoteTagShape::YellowXWithDots => {
+                self.icon_colored(style, ICON_X_WITH_DOTS, COLOR_YELLOW)
+            }
+            NoteTagShape::YellowX => self.icon_colored(style, ICON_X, COLOR_YELLOW),
+            NoteTagShape::FollowUpTodayFlag => self.icon_colored(style, ICON_FLAG, COLOR_RED),
+            NoteTagShape::FollowUpTomorrowFlag => self.icon_colored(style, ICON_FLAG, COLOR_RED),
+            NoteTagShape::FollowUpThisWeekFlag => self.icon_colored(style, ICON_FLAG, COLOR_RED),
+            NoteTagShape::FollowUpNextWeekFlag => self.icon_colored(style, ICON_FLAG, COLOR_RED),
+            NoteTagShape::NoFollowUpDateFlag => self.icon_colored(style, ICON_FLAG, COLOR_RED),
             NoteTagShape::BluePersonCheckBox => {
                 self.icon_checkbox_with_person(status, style, COLOR_BLUE)
             }
@@ -287,23 +389,27 @@ impl<'a> Renderer<'a> {
                 Cow::from(ICON_EMAIL),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::ClosedEnvelope => unimplemented!(),
-            NoteTagShape::OpenEnvelope => unimplemented!(),
-            NoteTagShape::MobilePhone => unimplemented!(),
-            NoteTagShape::TelephoneWithClock => unimplemented!(),
-            NoteTagShape::QuestionBalloon => unimplemented!(),
-            NoteTagShape::PaperClip => unimplemented!(),
-            NoteTagShape::FrowningFace => unimplemented!(),
-            NoteTagShape::InstantMessagingContactPerson => unimplemented!(),
-            NoteTagShape::PersonWithExclamationMark => unimplemented!(),
-            NoteTagShape::TwoPeople => unimplemented!(),
-            NoteTagShape::ReminderBell => unimplemented!(),
+            NoteTagShape::ClosedEnvelope => self.icon_plain(style, ICON_ENVELOPE_CLOSED),
+            NoteTagShape::OpenEnvelope => self.icon_plain(style, ICON_ENVELOPE_OPEN),
+            NoteTagShape::MobilePhone => self.icon_plain(style, ICON_MOBILE_PHONE),
+            NoteTagShape::TelephoneWithClock => self.icon_plain(style, ICON_PHONE),
+            NoteTagShape::QuestionBalloon => self.icon_plain(style, ICON_QUESTION_BALLOON),
+            NoteTagShape::PaperClip => self.icon_plain(style, ICON_PAPER_CLIP),
+            NoteTagShape::FrowningFace => {
+                self.icon_colored(style, ICON_FROWNING_FACE, COLOR_YELLOW)
+            }
+            NoteTagShape::InstantMessagingContactPerson => self.icon_plain(style, ICON_PERSON),
+            NoteTagShape::PersonWithExclamationMark => {
+                self.icon_with_badge(style, ICON_PERSON, "!")
+            }
+            NoteTagShape::TwoPeople => self.icon_plain(style, ICON_TWO_PEOPLE),
+            NoteTagShape::ReminderBell => self.icon_plain(style, ICON_BELL),
             NoteTagShape::Contact => (
                 Cow::from(ICON_CONTACT),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::RoseOnAStem => unimplemented!(),
-            NoteTagShape::CalendarDateWithClock => unimplemented!(),
+            NoteTagShape::RoseOnAStem => self.icon_colored(style, ICON_FLOWER, COLOR_RED),
+            NoteTagShape::CalendarDateWithClock => self.icon_plain(style, ICON_CALENDAR),
             NoteTagShape::MusicalNote => (
                 Cow::from(ICON_MUSIC),
                 self.icon_style(IconSize::Normal, style),
@@ -312,17 +418,17 @@ impl<'a> Renderer<'a> {
                 Cow::from(ICON_FILM),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::QuotationMark => unimplemented!(),
-            NoteTagShape::Globe => unimplemented!(),
+            NoteTagShape::QuotationMark => self.icon_plain(style, ICON_QUOTATION_MARK),
+            NoteTagShape::Globe => self.icon_plain(style, ICON_GLOBE),
             NoteTagShape::HyperlinkGlobe => (
                 Cow::from(ICON_LINK),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::Laptop => unimplemented!(),
-            NoteTagShape::Plane => unimplemented!(),
-            NoteTagShape::Car => unimplemented!(),
-            NoteTagShape::Binoculars => unimplemented!(),
-            NoteTagShape::PresentationSlide => unimplemented!(),
+            NoteTagShape::Laptop => self.icon_plain(style, ICON_LAPTOP),
+            NoteTagShape::Plane => self.icon_plain(style, ICON_PLANE),
+            NoteTagShape::Car => self.icon_plain(style, ICON_CAR),
+            NoteTagShape::Binoculars => self.icon_plain(style, ICON_BINOCULARS),
+            NoteTagShape::PresentationSlide => self.icon_plain(style, ICON_PRESENTATION),
             NoteTagShape::Padlock => (
                 Cow::from(ICON_LOCK),
                 self.icon_style(IconSize::Normal, style),
@@ -331,23 +437,25 @@ impl<'a> Renderer<'a> {
                 Cow::from(ICON_BOOK),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::NotebookWithClock => unimplemented!(),
+            NoteTagShape::NotebookWithClock => self.icon_plain(style, ICON_BOOK),
             NoteTagShape::BlankPaperWithLines => (
                 Cow::from(ICON_PAPER),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::Research => unimplemented!(),
+            NoteTagShape::Research => self.icon_plain(style, ICON_RESEARCH),
             NoteTagShape::Pen => (
                 Cow::from(ICON_PEN),
                 self.icon_style(IconSize::Normal, style),
             ),
-            NoteTagShape::DollarSign => unimplemented!(),
-            NoteTagShape::CoinsWithAWindowBackdrop => unimplemented!(),
-            NoteTagShape::ScheduledTask => unimplemented!(),
-            NoteTagShape::LightningBolt => unimplemented!(),
-            NoteTagShape::Cloud => unimplemented!(),
-            NoteTagShape::Heart => unimplemented!(),
-            NoteTagShape::Sunflower => unimplemented!(),
+            NoteTagShape::DollarSign => self.icon_plain(style, ICON_DOLLAR),
+            NoteTagShape::CoinsWithAWindowBackdrop => self.icon_plain(style, ICON_COINS),
+            NoteTagShape::ScheduledTask => self.icon_plain(style, ICON_CLOCK),
+            NoteTagShape::LightningBolt => {
+                self.icon_colored(style, ICON_LIGHTNING_BOLT, COLOR_YELLOW)
+            }
+            NoteTagShape::Cloud => self.icon_colored(style, ICON_CLOUD, COLOR_BLUE),
+            NoteTagShape::Heart => self.icon_colored(style, ICON_HEART, COLOR_RED),
+            NoteTagShape::Sunflower => self.icon_colored(style, ICON_FLOWER, COLOR_YELLOW),
         }
     }
 
@@ -507,6 +615,69 @@ impl<'a> Renderer<'a> {
         )
     }
 
+    fn icon_plain(&self, style: StyleSet, icon: &'static str) -> (Cow<'static, str>, StyleSet) {
+        (Cow::from(icon), self.icon_style(IconSize::Normal, style))
+    }
+
+    fn icon_colored(
+        &self,
+        mut style: StyleSet,
+        icon: &'static str,
+        color: &'static str,
+    ) -> (Cow<'static, str>, StyleSet) {
+        style.set("fill", color.to_string());
+
+        self.icon_plain(style, icon)
+    }
+
+    /// An arrow icon pointing right, rotated clockwise by `degrees`.
+    fn icon_arrow(
+        &self,
+        mut style: StyleSet,
+        icon: &'static str,
+        color: &'static str,
+        degrees: u16,
+    ) -> (Cow<'static, str>, StyleSet) {
+        if degrees != 0 {
+            style.set("transform", format!("rotate({}deg)", degrees));
+        }
+
+        self.icon_colored(style, icon, color)
+    }
+
+    fn icon_numbered_circle(
+        &self,
+        mut style: StyleSet,
+        color: &'static str,
+        number: u8,
+    ) -> (Cow<'static, str>, StyleSet) {
+        style.set("fill", color.to_string());
+
+        let icon = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 24 24\">\
+             <circle cx=\"12\" cy=\"12\" r=\"10\"/>\
+             <text x=\"12\" y=\"16.5\" text-anchor=\"middle\" font-family=\"sans-serif\" \
+             font-size=\"13\" font-weight=\"bold\" fill=\"white\">{}</text></svg>",
+            number
+        );
+
+        (Cow::from(icon), self.icon_style(IconSize::Normal, style))
+    }
+
+    fn icon_with_badge(
+        &self,
+        style: StyleSet,
+        icon: &'static str,
+        badge: &'static str,
+    ) -> (Cow<'static, str>, StyleSet) {
+        let content = format!(
+            "{}<span class=\"icon-secondary\"><span class=\"content\">{}</span></span>",
+            icon, badge
+        );
+
+        (Cow::from(content), self.icon_style(IconSize::Normal, style))
+    }
+
     fn icon_style(&self, size: IconSize, mut style: StyleSet) -> StyleSet {
         match size {
             IconSize::Normal => {
@@ -537,3 +708,30 @@ impl<'a> Renderer<'a> {
         style
     }
 }
+
+/// Annotate a tagged element with the labels of its note tags (`data-tag`)
+/// and whether they are completed (`data-tag-completed`). Returns whether
+/// the element has any note tags.
+pub(crate) fn set_note_tag_attributes(attrs: &mut AttributeSet, note_tags: &[NoteTag]) -> bool {
+    let labels = note_tags
+        .iter()
+        .filter_map(|tag| tag.definition())
+        .map(|def| slugify(def.label()))
+        .filter(|label| !label.is_empty())
+        .join(" ");
+
+    if labels.is_empty() {
+        return false;
+    }
+
+    let completed = note_tags.iter().any(|tag| tag.item_status().completed());
+
+    attrs.set("data-tag", labels);
+    attrs.set("data-tag-completed", completed.to_string());
+
+    true
+}
+
+fn is_checkbox(shape: NoteTagShape) -> bool {
+    format!("{:?}", shape).contains("CheckBox")
+}
@@ -0,0 +1,33 @@
+use crate::templates::html_escape;
+use color_eyre::eyre::{Result, WrapErr};
+use once_cell::sync::OnceCell;
+use onenote_parser::property::note_tag::NoteTagShape;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+static NOTE_TAG_MAP: OnceCell<HashMap<String, String>> = OnceCell::new();
+
+/// Load the TOML file `path` that maps note tags, given by their label or
+/// symbol name (e.g. `YellowStar`), to the HTML they are rendered as. The
+/// mapping can only be set once.
+pub(crate) fn init(path: &Path) -> Result<()> {
+    let data = fs::read_to_string(path).wrap_err("Failed to read note tag mapping")?;
+    let map: HashMap<String, String> =
+        toml::from_str(&data).wrap_err("Failed to parse note tag mapping")?;
+
+    let _ = NOTE_TAG_MAP.set(map);
+
+    Ok(())
+}
+
+/// The custom HTML for a note tag, if it's mapped. The label takes precedence
+/// over the symbol and `{label}` is replaced with the tag's label.
+pub(crate) fn get(label: &str, shape: NoteTagShape) -> Option<String> {
+    let map = NOTE_TAG_MAP.get()?;
+    let html = map
+        .get(label)
+        .or_else(|| map.get(&format!("{:?}", shape)))?;
+
+    Some(html.replace("{label}", &html_escape(label).unwrap_or_default()))
+}
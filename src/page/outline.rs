@@ -1,24 +1,45 @@
-use crate::page::Renderer;
-use crate::utils::{px, AttributeSet, StyleSet};
+use crate::page::text::rich_text_text;
+use crate::page::{with_text_rules, Renderer};
+use crate::utils::{fnv1a, px, AttributeSet, StyleSet};
 use color_eyre::Result;
+use itertools::Itertools;
 use onenote_parser::contents::{Outline, OutlineElement, OutlineItem};
 
 impl<'a> Renderer<'a> {
     pub(crate) fn render_outline(&mut self, outline: &Outline) -> Result<String> {
+        let mut contents = String::new();
+
+        contents.push_str(&format!("<div {}>", self.outline_attrs(outline, false)));
+        contents.push_str(&self.render_outline_items(
+            outline.items(),
+            0,
+            outline.child_level(),
+            outline.indents(),
+        )?);
+        contents.push_str("</div>");
+
+        Ok(contents)
+    }
+
+    /// Build the attributes of an outline's container.
+    ///
+    /// If `continued` is set, the outline continues from a previous part of a
+    /// split page and thus isn't positioned vertically.
+    pub(crate) fn outline_attrs(&mut self, outline: &Outline, continued: bool) -> AttributeSet {
         let mut attrs = AttributeSet::new();
         let mut styles = StyleSet::new();
-        let mut contents = String::new();
 
         attrs.set("class", "container-outline".to_string());
 
+        // Outlines resized by the user keep their width, so their text wraps
+        // at the same points as in OneNote. Other outlines grow with their
+        // content up to their maximum width.
         if let Some(width) = outline.layout_max_width() {
-            let outline_width = if outline.is_layout_size_set_by_user() {
-                width
+            if outline.is_layout_size_set_by_user() {
+                styles.set("width", px(width));
             } else {
-                width.max(13.0)
-            };
-
-            styles.set("max-width", px(outline_width));
+                styles.set("max-width", px(width.max(13.0)));
+            }
         };
 
         if outline.offset_horizontal().is_some() || outline.offset_vertical().is_some() {
@@ -29,24 +50,15 @@ impl<'a> Renderer<'a> {
             styles.set("left", px(offset));
         }
 
-        if let Some(offset) = outline.offset_vertical() {
+        if let Some(offset) = outline.offset_vertical().filter(|_| !continued) {
             styles.set("top", px(offset));
         }
 
-        if styles.len() > 0 {
-            attrs.set("style", styles.to_string());
-        }
+        self.set_styles(&mut attrs, styles);
 
-        contents.push_str(&format!("<div {}>", attrs));
-        contents.push_str(&self.render_outline_items(
-            outline.items(),
-            0,
-            outline.child_level(),
-            outline.indents(),
-        )?);
-        contents.push_str("</div>");
+        self.set_data_attributes(&mut attrs, "outline");
 
-        Ok(contents)
+        attrs
     }
 
     pub(crate) fn render_outline_items(
@@ -76,13 +88,23 @@ impl<'a> Renderer<'a> {
 
         let mut contents = String::new();
         let is_list = self.is_list(element);
+        let children = element.children();
+        let collapsible = self.section.opt.outline_controls && !children.is_empty();
 
         let mut attrs = AttributeSet::new();
-        attrs.set("class", "outline-element".to_string());
+        attrs.set("id", self.anchor(element));
+
+        if collapsible {
+            attrs.set("class", "outline-element has-children".to_string());
+        } else {
+            attrs.set("class", "outline-element".to_string());
+        }
 
         let mut styles = StyleSet::new();
         styles.set("margin-left", px(indent_width as f32));
-        attrs.set("style", styles.to_string());
+        self.set_styles(&mut attrs, styles);
+
+        self.set_data_attributes(&mut attrs, "outline-element");
 
         if is_list {
             contents.push_str(&format!("<li {}>", attrs));
@@ -107,15 +129,22 @@ impl<'a> Renderer<'a> {
             contents.push_str("</div>");
         }
 
-        let children = element.children();
-
         if !children.is_empty() {
-            contents.push_str(&self.render_outline_items(
+            let children = self.render_outline_items(
                 children,
                 current_level,
                 current_level + element.child_level(),
                 indents,
-            )?);
+            )?;
+
+            if collapsible {
+                contents.push_str(&format!(
+                    "<div class=\"outline-children\">{}</div>",
+                    children
+                ));
+            } else {
+                contents.push_str(&children);
+            }
         }
 
         if is_list {
@@ -126,6 +155,23 @@ impl<'a> Renderer<'a> {
 
         Ok(contents)
     }
+
+    /// Generate an ID for an outline element from a hash of its text (with
+    /// the text rules applied), so links to it keep working when the page is
+    /// converted again. Repeated IDs are numbered.
+    fn anchor(&mut self, element: &OutlineElement) -> String {
+        let text = element
+            .contents()
+            .iter()
+            .filter_map(|content| content.rich_text())
+            .map(rich_text_text)
+            .join("\n");
+
+        let text = with_text_rules(&text, &self.section.name);
+        let hash = fnv1a(text.as_bytes());
+
+        self.anchors.unique(format!("p-{:012x}", hash >> 16))
+    }
 }
 
 fn flatten_outline_items<'a>(
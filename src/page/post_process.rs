@@ -0,0 +1,86 @@
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The placeholder for the path of the written page in post-processing
+/// commands.
+const PATH_PLACEHOLDER: &str = "{path}";
+
+/// Whether the post-processing `command` is run on the written file instead
+/// of transforming the page's content.
+pub(crate) fn runs_on_file(command: &str) -> bool {
+    command.contains(PATH_PLACEHOLDER)
+}
+
+/// Run `command` on the written page at `path`.
+pub(crate) fn run_on_file(command: &str, path: &Path) -> Result<()> {
+    let command = command.replace(PATH_PLACEHOLDER, &shell_quote(&path.to_string_lossy()));
+    let status = shell(&command)
+        .status()
+        .wrap_err_with(|| format!("Failed to run post-processing command: {}", command))?;
+
+    if !status.success() {
+        return Err(eyre!(
+            "Post-processing command failed with {}: {}",
+            status,
+            command
+        ));
+    }
+
+    Ok(())
+}
+
+/// Pass `content` to `command` on stdin and return its output.
+pub(crate) fn run_on_content(command: &str, content: String) -> Result<String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .wrap_err_with(|| format!("Failed to run post-processing command: {}", command))?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = std::thread::spawn(move || stdin.write_all(content.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .wrap_err("Failed to read the output of the post-processing command")?;
+    writer
+        .join()
+        .expect("post-processing writer panicked")
+        .wrap_err("Failed to pass the page to the post-processing command")?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "Post-processing command failed with {}: {}",
+            output.status,
+            command
+        ));
+    }
+
+    String::from_utf8(output.stdout).wrap_err("Post-processing command returned invalid UTF-8")
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(unix)]
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(windows)]
+fn shell_quote(arg: &str) -> String {
+    format!("\"{}\"", arg)
+}
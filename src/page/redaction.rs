@@ -0,0 +1,59 @@
+use crate::page::text_rule::{parse_rule, read_rules, TextRule};
+use color_eyre::eyre::{Result, WrapErr};
+use once_cell::sync::OnceCell;
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static RULES: OnceCell<Vec<TextRule>> = OnceCell::new();
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+const DEFAULT_REPLACEMENT: &str = "[REDACTED]";
+
+/// Load the redaction rules from the TOML file `path`. Each entry in its
+/// `rules` array has either a `pattern` (a regular expression) or a `text`
+/// to redact, and an optional `replacement`. The rules can only be set once.
+pub(crate) fn init(path: &Path) -> Result<()> {
+    let rules = read_rules(path, "redaction rules")?
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            parse_rule(rule, Some(DEFAULT_REPLACEMENT))
+                .wrap_err_with(|| format!("Invalid redaction rule {}", i + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let _ = RULES.set(rules);
+
+    Ok(())
+}
+
+/// Apply the redaction rules to `text` and return the redacted text along
+/// with the number of redactions.
+pub(crate) fn redact(text: &str) -> (Cow<str>, usize) {
+    let mut text = Cow::Borrowed(text);
+    let count = RULES.get().map_or(0, |rules| {
+        rules.iter().map(|rule| rule.apply(&mut text)).sum()
+    });
+
+    TOTAL.fetch_add(count, Ordering::Relaxed);
+
+    (text, count)
+}
+
+/// Apply the redaction rules to a text that is also rendered (and counted)
+/// elsewhere, e.g. a page title that's repeated in file names and indexes.
+pub(crate) fn redact_uncounted(text: &str) -> Cow<str> {
+    let mut text = Cow::Borrowed(text);
+
+    for rule in RULES.get().into_iter().flatten() {
+        rule.apply(&mut text);
+    }
+
+    text
+}
+
+/// The number of redactions made since the last call.
+pub(crate) fn take_total() -> usize {
+    TOTAL.swap(0, Ordering::Relaxed)
+}
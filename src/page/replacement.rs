@@ -0,0 +1,65 @@
+use crate::page::text_rule::{parse_rule, read_rules, TextRule};
+use color_eyre::eyre::{Result, WrapErr};
+use once_cell::sync::OnceCell;
+use std::borrow::Cow;
+use std::path::Path;
+
+static RULES: OnceCell<Vec<ScopedRule>> = OnceCell::new();
+
+struct ScopedRule {
+    rule: TextRule,
+    sections: Option<Vec<String>>,
+}
+
+/// Load the find-and-replace rules from the TOML file `path`. Each entry in
+/// its `rules` array has either a `pattern` (a regular expression) or a
+/// `text` to find, a `replacement` and optionally the names of the
+/// `sections` it applies to. The rules can only be set once.
+pub(crate) fn init(path: &Path) -> Result<()> {
+    let rules = read_rules(path, "replacement rules")?
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            parse_scoped_rule(rule).wrap_err_with(|| format!("Invalid replacement rule {}", i + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let _ = RULES.set(rules);
+
+    Ok(())
+}
+
+fn parse_scoped_rule(rule: &toml::Value) -> Result<ScopedRule> {
+    let sections = rule
+        .get("sections")
+        .and_then(|sections| sections.as_array());
+
+    Ok(ScopedRule {
+        rule: parse_rule(rule, None)?,
+        sections: sections.map(|sections| {
+            sections
+                .iter()
+                .filter_map(|name| name.as_str())
+                .map(|name| name.to_string())
+                .collect()
+        }),
+    })
+}
+
+/// Apply the replacement rules for the section named `section` to `text`.
+pub(crate) fn replace<'t>(text: &'t str, section: &str) -> Cow<'t, str> {
+    let mut text = Cow::Borrowed(text);
+
+    for scoped in RULES.get().into_iter().flatten() {
+        let applies = match &scoped.sections {
+            Some(sections) => sections.iter().any(|name| name == section),
+            None => true,
+        };
+
+        if applies {
+            scoped.rule.apply(&mut text);
+        }
+    }
+
+    text
+}
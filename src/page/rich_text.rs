@@ -1,8 +1,11 @@
+use crate::page::note_tag::set_note_tag_attributes;
+use crate::page::text::render_hyphens;
 use crate::page::Renderer;
-use crate::utils::{px, AttributeSet, StyleSet};
+use crate::utils::{css_var_color, highlight_color, px, AttributeSet, StyleSet};
 use color_eyre::eyre::ContextCompat;
 use color_eyre::Result;
 use itertools::Itertools;
+use log::warn;
 use once_cell::sync::Lazy;
 use onenote_parser::contents::{EmbeddedObject, RichText};
 use onenote_parser::property::common::ColorRef;
@@ -11,6 +14,10 @@ use regex::{Captures, Regex};
 
 impl<'a> Renderer<'a> {
     pub(crate) fn render_rich_text(&mut self, text: &RichText) -> Result<String> {
+        if text.embedded_objects().is_empty() && is_horizontal_rule(text.text()) {
+            return Ok(self.render_horizontal_rule(text));
+        }
+
         let mut content = String::new();
         let mut attrs = AttributeSet::new();
         let mut style = self.parse_paragraph_styles(text);
@@ -20,28 +27,44 @@ impl<'a> Renderer<'a> {
             style.extend(note_tag_styles);
         }
 
+        let has_note_tags = set_note_tag_attributes(&mut attrs, text.note_tags());
+
         content.push_str(&self.parse_content(text)?);
 
         if content.starts_with("http://") || content.starts_with("https://") {
             content = format!("<a href=\"{}\">{}</a>", content, content);
         }
 
-        if style.len() > 0 {
-            attrs.set("style", style.to_string());
+        let class = self.style_class(style);
+        let has_style = class.is_some();
+
+        if let Some(class) = class {
+            attrs.set("class", class);
         }
 
+        self.set_data_attributes(&mut attrs, "paragraph");
+
         match text.paragraph_style().style_id() {
             Some(t) if !self.in_list && is_tag(t) => {
                 Ok(format!("<{} {}>{}</{}>", t, attrs, content, t))
             }
-            _ if style.len() > 0 => Ok(format!("<span style=\"{}\">{}</span>", style, content)),
+            _ if has_style || has_note_tags || self.section.opt.data_attributes => {
+                Ok(format!("<span {}>{}</span>", attrs, content))
+            }
             _ => Ok(content),
         }
     }
 
+    /// Apply the text rules to a text run and render its hyphens.
+    fn render_text(&mut self, text: String) -> String {
+        let text = self.apply_text_rules(text);
+
+        render_hyphens(text, self.section.opt.strip_soft_hyphens)
+    }
+
     fn parse_content(&mut self, data: &RichText) -> Result<String> {
         if !data.embedded_objects().is_empty() {
-            return Ok(data
+            let ink = data
                 .embedded_objects()
                 .iter()
                 .map(|object| match object {
@@ -49,15 +72,14 @@ impl<'a> Renderer<'a> {
                         self.render_ink(container.ink(), container.bounding_box(), true)
                     }
                     EmbeddedObject::InkSpace(space) => {
-                        format!("<span class=\"ink-space\" style=\"padding-left: {}; padding-top: {};\"></span>",
-                                px(space.width()), px(space.height()))
+                        Ok(format!("<span class=\"ink-space\" style=\"padding-left: {}; padding-top: {};\"></span>",
+                                px(space.width()), px(space.height())))
                     }
                     EmbeddedObject::InkLineBreak => {
-                        "<span class=\"ink-linebreak\"><br></span>".to_string()
+                        Ok("<span class=\"ink-linebreak\"><br></span>".to_string())
                     }
                 })
-                .collect_vec()
-                .join(""));
+                .collect::<Result<String>>();
         }
 
         let indices = data.text_run_indices();
@@ -65,51 +87,88 @@ impl<'a> Renderer<'a> {
 
         let mut text = data.text().to_string();
 
+        if indices.is_empty() {
+            text = self.render_text(text);
+        }
+
         if text.is_empty() {
             text = "&nbsp;".to_string();
         }
 
         if indices.is_empty() {
-            return Ok(fix_newlines(&text));
+            let text = fix_newlines(&text);
+
+            return match styles.first().filter(|_| self.clean()) {
+                Some(style) => Ok(emphasize(style, text)),
+                None => Ok(text),
+            };
         }
 
-        assert!(indices.len() + 1 >= styles.len());
+        if indices.len() + 1 != styles.len() {
+            warn!(
+                "Paragraph has {} text runs but {} run formattings, some text may be unstyled",
+                indices.len() + 1,
+                styles.len()
+            );
+        }
 
-        // Split text into parts specified by indices
+        // Split text into parts specified by indices. OneNote stores the
+        // indices as UTF-16 code unit offsets, so we split the UTF-16 encoded
+        // text instead of the Rust string.
+        let text = text.encode_utf16().collect_vec();
         let mut parts: Vec<String> = vec![];
+        let mut start = 0;
 
-        for i in indices.iter().copied().rev() {
-            let part = text.chars().skip(i as usize).collect();
-            text = text.chars().take(i as usize).collect();
+        for i in indices.iter().copied() {
+            let end = (i as usize).clamp(start, text.len());
+            parts.push(String::from_utf16_lossy(&text[start..end]));
 
-            parts.push(part);
+            start = end;
         }
 
-        if !indices.is_empty() {
-            parts.push(text);
-        }
+        parts.push(String::from_utf16_lossy(&text[start..]));
 
         let mut in_hyperlink = false;
 
         let content = parts
             .into_iter()
-            .rev()
-            .zip(styles.iter())
-            .map(|(text, style)| {
+            .enumerate()
+            .map(|(i, text)| {
+                let text = self.render_text(text);
+
+                // Runs without matching formatting are rendered as plain text
+                let style = match styles.get(i) {
+                    Some(style) => style,
+                    None => {
+                        in_hyperlink = false;
+
+                        return Ok(text);
+                    }
+                };
+
                 if style.hyperlink() {
                     let text = self.render_hyperlink(text, style, in_hyperlink);
                     in_hyperlink = true;
 
                     text
+                } else if self.clean() {
+                    in_hyperlink = false;
+
+                    Ok(emphasize(style, text))
                 } else {
                     in_hyperlink = false;
 
-                    let style = self.parse_style(style);
+                    let mut styles = self.parse_style(style);
 
-                    if style.len() > 0 {
-                        Ok(format!("<span style=\"{}\">{}</span>", style, text))
+                    let text = if self.section.opt.semantic_inline {
+                        self.semantic_inline(style, &mut styles, text)
                     } else {
-                        Ok(text)
+                        text
+                    };
+
+                    match self.style_class(styles) {
+                        Some(class) => Ok(format!("<span class=\"{}\">{}</span>", class, text)),
+                        None => Ok(text),
                     }
                 }
             })
@@ -119,7 +178,7 @@ impl<'a> Renderer<'a> {
     }
 
     fn render_hyperlink(
-        &self,
+        &mut self,
         text: String,
         style: &ParagraphStyling,
         in_hyperlink: bool,
@@ -127,6 +186,10 @@ impl<'a> Renderer<'a> {
         const HYPERLINK_MARKER: &str = "\u{fddf}HYPERLINK \"";
 
         let style = self.parse_style(style);
+        let class = self
+            .style_class(style)
+            .map(|class| format!(" class=\"{}\"", class))
+            .unwrap_or_default();
 
         if text.starts_with(HYPERLINK_MARKER) {
             let url = text
@@ -135,17 +198,74 @@ impl<'a> Renderer<'a> {
                 .strip_suffix('"')
                 .wrap_err("Hyperlink has no end marker")?;
 
-            Ok(format!("<a href=\"{}\" style=\"{}\">", url, style))
+            Ok(format!("<a href=\"{}\"{}>", url, class))
         } else if in_hyperlink {
             Ok(text + "</a>")
         } else {
-            Ok(format!(
-                "<a href=\"{}\" style=\"{}\">{}</a>",
-                text, style, text
-            ))
+            Ok(format!("<a href=\"{}\"{}>{}</a>", text, class, text))
         }
     }
 
+    /// Render a paragraph that only consists of a line of dashes, underscores
+    /// or box drawing characters as a `<hr>` with the paragraph's color.
+    fn render_horizontal_rule(&mut self, text: &RichText) -> String {
+        let mut styles = StyleSet::new();
+
+        let color = text
+            .text_run_formatting()
+            .first()
+            .and_then(|style| style.font_color());
+
+        if let Some(ColorRef::Manual { r, g, b }) = color {
+            styles.set("border", "none".to_string());
+            styles.set(
+                "border-top",
+                format!("1px solid {}", css_var_color("color", None, r, g, b)),
+            );
+        }
+
+        match self.style_class(styles) {
+            Some(class) => format!("<hr class=\"{}\">", class),
+            None => "<hr>".to_string(),
+        }
+    }
+
+    /// Wrap the text in semantic elements (e.g. `<strong>` for bold text)
+    /// and remove the styles they replace.
+    fn semantic_inline(
+        &mut self,
+        style: &ParagraphStyling,
+        styles: &mut StyleSet,
+        mut text: String,
+    ) -> String {
+        if let Some(background) = styles.get("background-color").map(str::to_string) {
+            styles.remove("background-color");
+
+            let mut mark_styles = StyleSet::new();
+            mark_styles.set("background-color", background);
+
+            text = match self.style_class(mark_styles) {
+                Some(class) => format!("<mark class=\"{}\">{}</mark>", class, text),
+                None => format!("<mark>{}</mark>", text),
+            };
+        }
+
+        let tags = [
+            (style.bold(), "font-weight", "strong"),
+            (style.italic(), "font-style", "em"),
+            (style.strikethrough(), "text-decoration", "del"),
+            (style.superscript(), "vertical-align", "sup"),
+            (style.subscript(), "vertical-align", "sub"),
+        ];
+
+        for (_, property, tag) in tags.iter().filter(|(enabled, _, _)| *enabled) {
+            styles.remove(property);
+            text = format!("<{}>{}</{}>", tag, text, tag);
+        }
+
+        text
+    }
+
     fn parse_paragraph_styles(&self, text: &RichText) -> StyleSet {
         if !text.embedded_objects().is_empty() {
             assert_eq!(
@@ -223,11 +343,11 @@ impl<'a> Renderer<'a> {
         }
 
         if let Some(ColorRef::Manual { r, g, b }) = style.font_color() {
-            styles.set("color", format!("rgb({},{},{})", r, g, b));
+            styles.set("color", css_var_color("color", None, r, g, b));
         }
 
         if let Some(ColorRef::Manual { r, g, b }) = style.highlight() {
-            styles.set("background-color", format!("rgb({},{},{})", r, g, b));
+            styles.set("background-color", highlight_color(r, g, b));
         }
 
         if style.paragraph_alignment().is_some() {
@@ -270,13 +390,50 @@ impl<'a> Renderer<'a> {
     }
 }
 
+/// Wrap the text in the elements for its emphasis, e.g. `<strong>` for bold
+/// text.
+fn emphasize(style: &ParagraphStyling, mut text: String) -> String {
+    let tags = [
+        (style.bold(), "strong"),
+        (style.italic(), "em"),
+        (style.underline(), "u"),
+        (style.strikethrough(), "s"),
+        (style.superscript(), "sup"),
+        (style.subscript(), "sub"),
+    ];
+
+    for (_, tag) in tags.iter().filter(|(enabled, _)| *enabled) {
+        text = format!("<{}>{}</{}>", tag, text, tag);
+    }
+
+    text
+}
+
+/// The length of a divider line typed as text. Shorter runs (like `---` or
+/// `===`) are more likely to be content.
+const MIN_DIVIDER_LENGTH: usize = 10;
+
+/// Whether the text is a divider line typed with dashes, underscores, equals
+/// signs or box drawing characters.
+fn is_horizontal_rule(text: &str) -> bool {
+    let text = text.trim();
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some(c @ ('-' | '_' | '=' | '\u{2014}' | '\u{2500}' | '\u{2501}' | '\u{2550}')) => {
+            text.chars().count() >= MIN_DIVIDER_LENGTH && chars.all(|other| other == c)
+        }
+        _ => false,
+    }
+}
+
 fn is_tag(tag: &str) -> bool {
     !matches!(tag, "PageDateTime" | "PageTitle")
 }
 
 fn fix_newlines(text: &str) -> String {
     static REGEX_LEADING_SPACES: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"<br>(\s+)").expect("failed to compile regex"));
+        Lazy::new(|| Regex::new(r"<br>([^\S\t]+)").expect("failed to compile regex"));
 
     let text = text
         .replace("\u{000b}", "<br>")
@@ -287,5 +444,6 @@ fn fix_newlines(text: &str) -> String {
         .replace_all(&text, |captures: &Captures| {
             "<br>".to_string() + &"&nbsp;".repeat(captures[1].len())
         })
-        .to_string()
+        // Keep tabs so they align to OneNote's default tab stops (every 0.5")
+        .replace('\t', "<span class=\"tab\">\t</span>")
 }
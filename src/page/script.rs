@@ -0,0 +1,60 @@
+use color_eyre::eyre::{eyre, Result};
+use once_cell::sync::OnceCell;
+use rhai::{Engine, Map, Scope, AST};
+use std::path::Path;
+
+static SCRIPT: OnceCell<(Engine, AST)> = OnceCell::new();
+
+/// The function a script defines to transform the pages.
+const TRANSFORM_FN: &str = "transform";
+
+/// Information about a page that's passed to the script.
+pub(crate) struct ScriptPage<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) section: &'a str,
+    pub(crate) file_name: &'a str,
+    pub(crate) extension: &'a str,
+}
+
+/// Compile the rhai script at `path`, which must define a
+/// `transform(content, page)` function. The script can only be set once.
+pub(crate) fn init(path: &Path) -> Result<()> {
+    let engine = Engine::new();
+    let ast = engine
+        .compile_file(path.to_path_buf())
+        .map_err(|err| eyre!("Failed to compile script {}: {}", path.display(), err))?;
+
+    if !ast
+        .iter_functions()
+        .any(|f| f.name == TRANSFORM_FN && f.params.len() == 2)
+    {
+        return Err(eyre!(
+            "Script {} doesn't define a {}(content, page) function",
+            path.display(),
+            TRANSFORM_FN
+        ));
+    }
+
+    let _ = SCRIPT.set((engine, ast));
+
+    Ok(())
+}
+
+/// Pass the rendered `content` of a page to the script and return the
+/// transformed content. Without a script, the content is returned unchanged.
+pub(crate) fn transform(content: String, page: &ScriptPage) -> Result<String> {
+    let (engine, ast) = match SCRIPT.get() {
+        Some(script) => script,
+        None => return Ok(content),
+    };
+
+    let mut info = Map::new();
+    info.insert("title".into(), page.title.into());
+    info.insert("section".into(), page.section.into());
+    info.insert("file_name".into(), page.file_name.into());
+    info.insert("extension".into(), page.extension.into());
+
+    engine
+        .call_fn(&mut Scope::new(), ast, TRANSFORM_FN, (content, info))
+        .map_err(|err| eyre!("Script failed on page {}: {}", page.file_name, err))
+}
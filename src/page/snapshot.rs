@@ -0,0 +1,61 @@
+use crate::cli::SnapshotFormat;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The viewport size of the browser window in CSS pixels.
+const WINDOW_SIZE: &str = "1280,1024";
+
+/// How long the browser may take to run the page's scripts and load its
+/// images, in milliseconds.
+const TIME_BUDGET: u32 = 5000;
+
+/// The name of the snapshot file of the page written to `file_name`.
+fn snapshot_file_name(file_name: &str, format: SnapshotFormat) -> String {
+    format!("{}.snapshot.{}", file_name, format.extension())
+}
+
+/// Render the written page at `path` with headless Chromium and store the
+/// snapshot next to it.
+pub(crate) fn render(chromium: &str, format: SnapshotFormat, path: &Path) -> Result<()> {
+    let path = fs::canonicalize(path).wrap_err("Failed to resolve page path")?;
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let snapshot_path = path.with_file_name(snapshot_file_name(&file_name, format));
+
+    let mut command = Command::new(chromium);
+    command
+        .arg("--headless")
+        .arg("--disable-gpu")
+        .arg("--hide-scrollbars")
+        .arg(format!("--window-size={}", WINDOW_SIZE))
+        .arg(format!("--virtual-time-budget={}", TIME_BUDGET));
+
+    match format {
+        SnapshotFormat::Png => {
+            command.arg(format!("--screenshot={}", snapshot_path.display()));
+        }
+        SnapshotFormat::Pdf => {
+            command
+                .arg("--no-pdf-header-footer")
+                .arg(format!("--print-to-pdf={}", snapshot_path.display()));
+        }
+    }
+
+    let output = command
+        .arg(&path)
+        .stdin(Stdio::null())
+        .output()
+        .wrap_err_with(|| format!("Failed to run {}", chromium))?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "Failed to render snapshot of {} with {}: {}",
+            path.display(),
+            chromium,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
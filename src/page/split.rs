@@ -0,0 +1,144 @@
+use crate::cli::SplitMode;
+use crate::page::Renderer;
+use color_eyre::Result;
+use itertools::Itertools;
+use onenote_parser::contents::{Outline, OutlineItem};
+use onenote_parser::page::PageContent;
+use std::collections::HashSet;
+
+/// A part of a page's content that is never split across files.
+struct Block {
+    /// The index of the outline this block is a part of
+    outline: Option<usize>,
+    heading: bool,
+    html: String,
+}
+
+impl<'a> Renderer<'a> {
+    /// Render the page contents, split into multiple parts according to `mode`.
+    pub(crate) fn render_split_contents(
+        &mut self,
+        contents: &[PageContent],
+        mode: SplitMode,
+    ) -> Result<Vec<String>> {
+        let mut blocks = Vec::new();
+
+        for (i, content) in contents.iter().enumerate() {
+            if let PageContent::Outline(outline) = content {
+                for items in self.group_outline_items(outline.items()) {
+                    blocks.push(Block {
+                        outline: Some(i),
+                        heading: starts_with_heading(items),
+                        html: self.render_outline_items(
+                            items,
+                            0,
+                            outline.child_level(),
+                            outline.indents(),
+                        )?,
+                    });
+                }
+            } else {
+                blocks.push(Block {
+                    outline: None,
+                    heading: false,
+                    html: self.render_page_content(content)?,
+                });
+            }
+        }
+
+        let max_size = self.section.opt.split_size * 1024;
+        let mut parts: Vec<Vec<Block>> = vec![Vec::new()];
+        let mut size = 0;
+
+        for block in blocks {
+            let current = parts.last().expect("no current part");
+
+            let split = !current.is_empty()
+                && match mode {
+                    SplitMode::Heading => block.heading,
+                    SplitMode::Size => size + block.html.len() > max_size,
+                };
+
+            if split {
+                parts.push(Vec::new());
+                size = 0;
+            }
+
+            size += block.html.len();
+            parts.last_mut().expect("no current part").push(block);
+        }
+
+        let mut started_outlines = HashSet::new();
+
+        Ok(parts
+            .into_iter()
+            .map(|blocks| {
+                let mut html = String::new();
+
+                for (outline, blocks) in &blocks.into_iter().group_by(|block| block.outline) {
+                    if let Some(index) = outline {
+                        let continued = !started_outlines.insert(index);
+                        let attrs = self.outline_attrs(outline_at(contents, index), continued);
+
+                        html.push_str(&format!("<div {}>", attrs));
+                        html.extend(blocks.map(|block| block.html));
+                        html.push_str("</div>");
+                    } else {
+                        html.extend(blocks.map(|block| block.html));
+                    }
+                }
+
+                html
+            })
+            .collect())
+    }
+
+    /// Group the top-level outline items into chunks that may be placed in
+    /// different parts. Consecutive list items are kept together so that list
+    /// numbering stays intact.
+    fn group_outline_items<'b>(&self, items: &'b [OutlineItem]) -> Vec<&'b [OutlineItem]> {
+        let mut groups = Vec::new();
+        let mut start = 0;
+
+        for i in 1..items.len() {
+            if !(self.is_list_item(&items[i - 1]) && self.is_list_item(&items[i])) {
+                groups.push(&items[start..i]);
+                start = i;
+            }
+        }
+
+        if start < items.len() {
+            groups.push(&items[start..]);
+        }
+
+        groups
+    }
+
+    fn is_list_item(&self, item: &OutlineItem) -> bool {
+        match item {
+            OutlineItem::Element(element) => self.is_list(element),
+            OutlineItem::Group(_) => false,
+        }
+    }
+}
+
+fn starts_with_heading(items: &[OutlineItem]) -> bool {
+    let element = match items.first() {
+        Some(OutlineItem::Element(element)) => element,
+        _ => return false,
+    };
+
+    element
+        .contents()
+        .first()
+        .and_then(|content| content.rich_text())
+        .and_then(|text| text.paragraph_style().style_id())
+        == Some("h1")
+}
+
+fn outline_at(contents: &[PageContent], index: usize) -> &Outline {
+    match &contents[index] {
+        PageContent::Outline(outline) => outline,
+        _ => unreachable!("page content is not an outline"),
+    }
+}
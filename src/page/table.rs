@@ -1,5 +1,5 @@
 use crate::page::Renderer;
-use crate::utils::{px, AttributeSet, StyleSet};
+use crate::utils::{css_var_color, px, AttributeSet, StyleSet};
 use color_eyre::Result;
 use onenote_parser::contents::{OutlineElement, Table, TableCell};
 
@@ -14,7 +14,7 @@ impl<'a> Renderer<'a> {
         }
 
         let mut attributes = AttributeSet::new();
-        attributes.set("style", styles.to_string());
+        self.set_styles(&mut attributes, styles);
         attributes.set("cellspacing", "0".to_string());
         attributes.set("cellpadding", "0".to_string());
 
@@ -22,6 +22,8 @@ impl<'a> Renderer<'a> {
             attributes.set("border", "1".to_string());
         }
 
+        self.set_data_attributes(&mut attributes, "table");
+
         content.push_str(&format!("<table {}>", attributes.to_string()));
 
         let locked_cols = calc_locked_cols(table.cols_locked(), table.cols());
@@ -78,12 +80,15 @@ impl<'a> Renderer<'a> {
         if let Some(color) = cell.background_color() {
             styles.set(
                 "background",
-                format!("rgb({}, {}, {})", color.r(), color.g(), color.b()),
+                css_var_color("bg", None, color.r(), color.g(), color.b()),
             )
         }
 
         let mut attrs = AttributeSet::new();
-        attrs.set("style", styles.to_string());
+
+        if let Some(class) = self.style_class(styles) {
+            attrs.set("class", class);
+        }
 
         contents.push_str(&format!("<td {}>", attrs.to_string()));
 
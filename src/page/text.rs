@@ -0,0 +1,119 @@
+use once_cell::sync::Lazy;
+use onenote_parser::contents::{Content, OutlineElement, OutlineItem, RichText, Table};
+use onenote_parser::page::{Page, PageContent};
+use regex::Regex;
+
+/// Extract the plain text of a page, including its title.
+pub(crate) fn page_text(page: &Page) -> String {
+    let mut text = String::new();
+
+    if let Some(title) = page.title_text() {
+        text.push_str(title.trim());
+        text.push_str("\n\n");
+    }
+
+    for content in page.contents() {
+        if let PageContent::Outline(outline) = content {
+            outline_items_text(outline.items(), 0, &mut text);
+            text.push('\n');
+        }
+    }
+
+    text.trim_end().to_string() + "\n"
+}
+
+/// Soft hyphens, as the Unicode character and as the control character that
+/// OneNote uses for optional hyphens.
+const SOFT_HYPHENS: [char; 2] = ['\u{ad}', '\u{1f}'];
+
+/// Non-breaking hyphens, as the Unicode character and as the control
+/// character that OneNote uses for them.
+const NON_BREAKING_HYPHENS: [char; 2] = ['\u{2011}', '\u{1e}'];
+
+/// Replace soft and non-breaking hyphens in a text by numeric character
+/// references, which (unlike `&shy;`) are also valid in XML exports. With
+/// `strip_soft_hyphens`, soft hyphens are removed instead.
+pub(crate) fn render_hyphens(text: String, strip_soft_hyphens: bool) -> String {
+    if !text.contains(&SOFT_HYPHENS[..]) && !text.contains(&NON_BREAKING_HYPHENS[..]) {
+        return text;
+    }
+
+    let soft_hyphen = if strip_soft_hyphens { "" } else { "&#173;" };
+
+    text.replace(&SOFT_HYPHENS[..], soft_hyphen)
+        .replace(&NON_BREAKING_HYPHENS[..], "&#8209;")
+}
+
+/// Count the words in a text.
+pub(crate) fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Estimate the reading time of a text with `words` words in minutes.
+pub(crate) fn reading_time(words: usize) -> usize {
+    const WORDS_PER_MINUTE: usize = 200;
+
+    ((words + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE).max(1)
+}
+
+/// Extract the plain text of a paragraph, without hyperlink markers.
+pub(crate) fn rich_text_text(text: &RichText) -> String {
+    static REGEX_HYPERLINK: Lazy<Regex> =
+        Lazy::new(|| Regex::new("\u{fddf}HYPERLINK \"[^\"]*\"").expect("failed to compile regex"));
+
+    REGEX_HYPERLINK
+        .replace_all(text.text(), "")
+        .replace('\u{000b}', "\n")
+        .replace('\r', "\n")
+}
+
+fn outline_items_text(items: &[OutlineItem], depth: usize, text: &mut String) {
+    for item in items {
+        match item {
+            OutlineItem::Element(element) => outline_element_text(element, depth, text),
+            OutlineItem::Group(group) => outline_items_text(group.outlines(), depth, text),
+        }
+    }
+}
+
+fn outline_element_text(element: &OutlineElement, depth: usize, text: &mut String) {
+    for content in element.contents() {
+        match content {
+            Content::RichText(rich_text) => {
+                let paragraph = rich_text_text(rich_text);
+
+                for line in paragraph.lines() {
+                    text.push_str(&"  ".repeat(depth));
+                    text.push_str(line);
+                    text.push('\n');
+                }
+            }
+            Content::Table(table) => table_text(table, depth, text),
+            _ => {}
+        }
+    }
+
+    outline_items_text(element.children(), depth + 1, text);
+}
+
+fn table_text(table: &Table, depth: usize, text: &mut String) {
+    for row in table.contents() {
+        let cells = row
+            .contents()
+            .iter()
+            .map(|cell| {
+                let mut cell_text = String::new();
+
+                for element in cell.contents() {
+                    outline_element_text(element, 0, &mut cell_text);
+                }
+
+                cell_text.trim().replace('\n', " ")
+            })
+            .collect::<Vec<_>>();
+
+        text.push_str(&"  ".repeat(depth));
+        text.push_str(&cells.join("\t"));
+        text.push('\n');
+    }
+}
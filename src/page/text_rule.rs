@@ -0,0 +1,78 @@
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use regex::{NoExpand, Regex};
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+/// A rule that replaces text matching a regular expression or a literal
+/// string.
+pub(crate) struct TextRule {
+    pattern: Regex,
+    replacement: String,
+    /// Whether the replacement is used as is, without expanding `$` groups
+    literal: bool,
+}
+
+impl TextRule {
+    /// Apply the rule to `text` and return the number of replacements.
+    pub(crate) fn apply(&self, text: &mut Cow<str>) -> usize {
+        let count = self.pattern.find_iter(text).count();
+
+        if count > 0 {
+            let replaced = if self.literal {
+                self.pattern
+                    .replace_all(text, NoExpand(&self.replacement))
+                    .into_owned()
+            } else {
+                self.pattern
+                    .replace_all(text, self.replacement.as_str())
+                    .into_owned()
+            };
+
+            *text = Cow::Owned(replaced);
+        }
+
+        count
+    }
+}
+
+/// Read the `rules` array of the TOML file `path`, e.g. redaction or
+/// replacement rules. `name` describes the rules in error messages.
+pub(crate) fn read_rules(path: &Path, name: &str) -> Result<Vec<toml::Value>> {
+    let data = fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", name))?;
+    let value: toml::Value =
+        toml::from_str(&data).wrap_err_with(|| format!("Failed to parse {}", name))?;
+
+    match value.get("rules") {
+        Some(toml::Value::Array(rules)) => Ok(rules.clone()),
+        _ => Err(eyre!("The {} have no rules array", name)),
+    }
+}
+
+/// Parse a rule with either a `pattern` (a regular expression) or a `text`
+/// and a `replacement`, which defaults to `default_replacement` if given.
+pub(crate) fn parse_rule(
+    rule: &toml::Value,
+    default_replacement: Option<&str>,
+) -> Result<TextRule> {
+    let field = |name| {
+        rule.get(name)
+            .and_then(|value: &toml::Value| value.as_str())
+    };
+
+    let (pattern, literal) = match (field("pattern"), field("text")) {
+        (Some(pattern), None) => (Regex::new(pattern)?, false),
+        (None, Some(text)) => (Regex::new(&regex::escape(text))?, true),
+        _ => return Err(eyre!("Expected either a pattern or a text")),
+    };
+
+    let replacement = field("replacement")
+        .or(default_replacement)
+        .ok_or_else(|| eyre!("Missing replacement"))?;
+
+    Ok(TextRule {
+        pattern,
+        replacement: replacement.to_string(),
+        literal,
+    })
+}
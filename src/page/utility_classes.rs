@@ -0,0 +1,48 @@
+use crate::cli::UtilityClasses;
+use crate::utils::StyleSet;
+
+/// Replace the styles that CSS frameworks have utility classes for with those
+/// classes. Returns the classes, the other styles remain in `styles`.
+pub(crate) fn extract(framework: UtilityClasses, styles: &mut StyleSet) -> Vec<String> {
+    let mut classes = Vec::new();
+
+    for (prop, value, class) in utility_classes(framework) {
+        if styles.get(prop) == Some(*value) {
+            styles.remove(prop);
+            classes.push(class.to_string());
+        }
+    }
+
+    classes
+}
+
+/// The utility classes of a framework with the style property and value they
+/// set.
+fn utility_classes(
+    framework: UtilityClasses,
+) -> &'static [(&'static str, &'static str, &'static str)] {
+    match framework {
+        UtilityClasses::Bootstrap => &[
+            ("font-weight", "bold", "fw-bold"),
+            ("font-style", "italic", "fst-italic"),
+            ("text-decoration", "underline", "text-decoration-underline"),
+            (
+                "text-decoration",
+                "line-through",
+                "text-decoration-line-through",
+            ),
+            ("text-align", "center", "text-center"),
+            ("text-align", "right", "text-end"),
+        ],
+        UtilityClasses::Tailwind => &[
+            ("font-weight", "bold", "font-bold"),
+            ("font-style", "italic", "italic"),
+            ("text-decoration", "underline", "underline"),
+            ("text-decoration", "line-through", "line-through"),
+            ("text-align", "center", "text-center"),
+            ("text-align", "right", "text-right"),
+            ("vertical-align", "super", "align-super"),
+            ("vertical-align", "sub", "align-sub"),
+        ],
+    }
+}
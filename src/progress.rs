@@ -0,0 +1,51 @@
+use crate::utils::json_string;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Report the progress as JSON events on stderr instead of showing spinners.
+pub(crate) fn init_json() {
+    JSON.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+pub(crate) fn section_started(section: &str, pages: usize) {
+    emit(&[
+        ("event", json_string("section_started")),
+        ("section", json_string(section)),
+        ("pages", pages.to_string()),
+    ]);
+}
+
+/// Report that the `index`th of `total` pages of a section has been converted.
+pub(crate) fn page_converted(section: &str, page: &str, index: usize, total: usize) {
+    emit(&[
+        ("event", json_string("page_converted")),
+        ("section", json_string(section)),
+        ("page", json_string(page)),
+        ("percentage", ((index + 1) * 100 / total.max(1)).to_string()),
+    ]);
+}
+
+pub(crate) fn asset_written(file: &str) {
+    emit(&[
+        ("event", json_string("asset_written")),
+        ("file", json_string(file)),
+    ]);
+}
+
+fn emit(fields: &[(&str, String)]) {
+    if !is_json() {
+        return;
+    }
+
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), value))
+        .collect();
+
+    eprintln!("{{{}}}", fields.join(","));
+}
@@ -0,0 +1,43 @@
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::Path;
+
+/// Convert a section (`.one` file) to HTML.
+///
+/// The keyword arguments are passed on as command line options, e.g.
+/// `hash_assets=True` becomes `--hash-assets` and `lang="de"` becomes
+/// `--lang de`. All conversions in a process must use the same options.
+#[pyfunction]
+#[pyo3(text_signature = "(path, output_dir, **options)")]
+#[args(options = "**")]
+fn convert_section(path: &str, output_dir: &str, options: Option<&PyDict>) -> PyResult<()> {
+    let mut args = Vec::new();
+
+    for (key, value) in options.into_iter().flatten() {
+        let option = format!("--{}", key.str()?.to_str()?.replace('_', "-"));
+
+        if value.is_none() {
+            continue;
+        }
+
+        match value.extract::<bool>() {
+            Ok(true) => args.push(option),
+            Ok(false) => {}
+            Err(_) => {
+                args.push(option);
+                args.push(value.str()?.to_str()?.to_string());
+            }
+        }
+    }
+
+    crate::convert_section(Path::new(path), Path::new(output_dir), args)
+        .map_err(|err| PyRuntimeError::new_err(format!("{:?}", err)))
+}
+
+#[pymodule]
+fn one2html(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(convert_section, module)?)?;
+
+    Ok(())
+}
@@ -1,66 +1,613 @@
-use crate::{page, templates};
-use color_eyre::eyre::Result;
+use crate::checkpoint;
+use crate::cli::{ConflictMode, MergeMode, Opt, OutputFormat};
+use crate::export::PageInfo;
+use crate::favicon;
+use crate::inventory::{self, InventoryRow};
+use crate::nav::{NavPage, NavSection};
+use crate::notebook::{css_color, prepare_color, RgbColor};
+use crate::page::asset_writer::AssetWriter;
+use crate::page::json_ld;
+use crate::progress;
+use crate::stats::{PageStats, SectionStats};
+use crate::status;
+use crate::strings::strings;
+use crate::templates::merged;
+use crate::templates::page::PageMeta;
+use crate::timings;
+use crate::utils::{onenote_link, slugify, url_path};
+use crate::{export, nav, page, stats, templates};
+use color_eyre::eyre::{eyre, Report, Result, WrapErr};
+use itertools::Itertools;
+use log::warn;
+use onenote_parser::errors::Error as ParserError;
+use onenote_parser::page::Page;
 use onenote_parser::section::Section;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// A page to render with its title, file name and the conflict pages that are
+/// appended to it.
+type SectionPage<'s> = (&'s Page, String, String, Vec<&'s Page>);
 
 pub(crate) struct Renderer {
-    pub(crate) files: HashSet<String>,
+    files: HashSet<String>,
+    /// The file names reserved by the page that's being rendered.
+    page_files: Vec<String>,
     pub(crate) pages: HashSet<String>,
+    pub(crate) assets: BTreeMap<String, String>,
+    failed: BTreeMap<String, String>,
+    word_counts: BTreeMap<String, (usize, usize)>,
+    redactions: BTreeMap<String, usize>,
+    pub(crate) opt: Opt,
+    /// The name of the section that's being rendered.
+    pub(crate) name: String,
+    /// The name of the notebook the section belongs to.
+    pub(crate) notebook: Option<String>,
+    pub(crate) color: Option<RgbColor>,
+    source: Option<PathBuf>,
+    pub(crate) asset_writer: Option<AssetWriter>,
 }
 
 impl Renderer {
-    pub fn new() -> Self {
+    pub fn new(opt: Opt) -> Self {
+        // Queued assets hold a copy of their data, so don't queue them in
+        // low-memory mode
+        let asset_writer = if opt.asset_threads > 1 && !opt.low_memory {
+            Some(AssetWriter::new(&opt, opt.asset_threads))
+        } else {
+            None
+        };
+
         Renderer {
             files: Default::default(),
+            page_files: Default::default(),
             pages: Default::default(),
+            assets: Default::default(),
+            failed: Default::default(),
+            word_counts: Default::default(),
+            redactions: Default::default(),
+            opt,
+            name: String::new(),
+            notebook: None,
+            color: None,
+            source: None,
+            asset_writer,
         }
     }
 
-    pub fn render(&mut self, section: &Section, output_dir: &Path) -> Result<PathBuf> {
-        let section_dir = output_dir.join(sanitize_filename::sanitize(section.display_name()));
+    /// Set the name of the notebook the section belongs to.
+    pub(crate) fn set_notebook(&mut self, name: String) {
+        self.notebook = Some(name);
+    }
 
-        if !section_dir.is_dir() {
-            fs::create_dir(&section_dir)?;
+    /// Set the path of the section file that's being rendered.
+    pub(crate) fn set_source(&mut self, path: PathBuf) {
+        self.source = Some(path);
+    }
+
+    pub fn render(&mut self, section: &Section, output_dir: &Path) -> Result<PathBuf> {
+        if self.opt.merge == Some(MergeMode::Section) {
+            return self.render_merged(section, output_dir);
         }
 
+        self.name = section.display_name().to_string();
+        self.color = section.color().map(prepare_color);
+
+        let section_dir = self.section_dir(section, output_dir)?;
+
         let mut toc = Vec::new();
-        let mut fallback_title_index = 0;
+        let mut conflicts = Vec::new();
+        let mut nav_pages = Vec::new();
+        let mut page_stats = Vec::new();
+
+        let output_root = self.opt.output.clone();
+        let completed = match &output_root {
+            Some(root) if self.opt.resume => checkpoint::load(root)?,
+            _ => HashMap::new(),
+        };
+
+        let pages = self.pages(section)?;
+        let total = pages.len();
+        progress::section_started(section.display_name(), total);
+
+        for (i, (page, title, file_name, appended)) in pages.into_iter().enumerate() {
+            let output_file = export::page_dir(&self.opt, &section_dir)
+                .join(export::page_file_name(self.opt.format, &file_name));
+            let checkpoint_path = output_root
+                .as_ref()
+                .and_then(|root| output_file.strip_prefix(root).ok())
+                .map(|path| path.to_string_lossy().to_string());
 
-        for page_series in section.page_series() {
-            for page in page_series.pages() {
-                let title = page.title_text().map(|s| s.to_string()).unwrap_or_else(|| {
-                    fallback_title_index += 1;
+            match checkpoint_path
+                .as_ref()
+                .and_then(|path| completed.get(path))
+            {
+                // Keep later pages from overwriting the files of completed pages
+                Some(files) => self.files.extend(files.iter().cloned()),
+                None => {
+                    self.page_files.clear();
+                    status::set_current_page(Some(&title));
+                    self.write_page(page, &appended, &title, &file_name, &section_dir)?;
+                    status::set_current_page(None);
 
-                    format!("Untitled Page {}", fallback_title_index)
+                    if let Some((root, path)) = output_root.as_ref().zip(checkpoint_path) {
+                        checkpoint::record(root, &path, &self.page_files)?;
+                    }
+                }
+            }
+
+            progress::page_converted(section.display_name(), &title, i, total);
+
+            if let Some(root) = output_root.as_ref().filter(|_| self.opt.nav_json) {
+                nav_pages.push(NavPage {
+                    title: title.clone(),
+                    level: page.level(),
+                    path: url_path(output_file.strip_prefix(root)?),
+                    tags: json_ld::page_note_tag_labels(page),
                 });
+            }
 
-                let file_name = title.trim().replace("/", "_");
-                let file_name = self.determine_page_filename(&file_name)?;
-                let file_name = sanitize_filename::sanitize(file_name + ".html");
+            if self.opt.stats || self.opt.inventory.is_some() {
+                let stats = PageStats::collect(page, &title);
 
-                let output_file = section_dir.join(file_name);
+                if self.opt.inventory.is_some() {
+                    let path = match &output_root {
+                        Some(root) => output_file.strip_prefix(root)?,
+                        None => &output_file,
+                    };
 
-                let mut renderer = page::Renderer::new(section_dir.clone(), self);
-                let output = renderer.render_page(page)?;
+                    inventory::record_page(InventoryRow {
+                        section: section.display_name().to_string(),
+                        title: title.clone(),
+                        words: stats.words,
+                        attachments: stats.attachments,
+                        path: path.to_string_lossy().to_string(),
+                    });
+                }
+
+                if self.opt.stats {
+                    page_stats.push(stats);
+                }
+            }
+
+            let path = output_file
+                .strip_prefix(&output_dir)?
+                .to_string_lossy()
+                .to_string();
+
+            if self.opt.conflicts == Some(ConflictMode::Separate) && is_conflict_page(page) {
+                conflicts.push((title, path));
+            } else {
+                let thumbnail = export::asset_dir(&self.opt, &section_dir)
+                    .join(page::thumbnail_file_name(&file_name));
+                let thumbnail = if self.opt.thumbnails && thumbnail.is_file() {
+                    Some(
+                        thumbnail
+                            .strip_prefix(&output_dir)?
+                            .to_string_lossy()
+                            .to_string(),
+                    )
+                } else {
+                    None
+                };
+
+                toc.push((title, path, page.level(), thumbnail));
+            }
+        }
+
+        let favicon_dir = if self.opt.favicon {
+            favicon::write(&section_dir, self.color.as_ref())?;
+
+            Some(format!(
+                "{}/",
+                section_dir.strip_prefix(&output_dir)?.to_string_lossy()
+            ))
+        } else {
+            None
+        };
+
+        let toc_html = templates::section::render(
+            section.display_name(),
+            toc,
+            conflicts,
+            self.color.as_ref().map(css_color),
+            favicon_dir,
+        )?;
+        let toc_file = output_dir.join(format!("{}.html", file_stem(section.display_name())));
+        fs::write(&toc_file, toc_html)?;
+
+        if let Some(root) = output_root.as_ref().filter(|_| self.opt.nav_json) {
+            nav::record_section(NavSection {
+                name: section.display_name().to_string(),
+                path: url_path(section_dir.strip_prefix(root)?),
+                index: url_path(toc_file.strip_prefix(root)?),
+                pages: nav_pages,
+            });
+        }
+
+        if self.opt.stats {
+            stats::record_section(SectionStats {
+                name: section.display_name().to_string(),
+                pages: page_stats,
+            });
+        }
+
+        self.finish_assets()?;
+        self.write_asset_manifest(&section_dir)?;
+
+        Ok(section_dir)
+    }
 
-                fs::write(&output_file, output)?;
+    /// Render a page and write its files to `section_dir`.
+    fn write_page(
+        &mut self,
+        page: &Page,
+        conflicts: &[&Page],
+        title: &str,
+        file_name: &str,
+        section_dir: &Path,
+    ) -> Result<()> {
+        let mut renderer = page::Renderer::new(section_dir.to_path_buf(), self);
 
-                toc.push((
+        let start = Instant::now();
+        let result = catch_panic(|| renderer.render_page(page, conflicts, file_name));
+        let name = section_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        timings::record_page(
+            &format!("{}/{}", name, file_name),
+            start.elapsed(),
+            renderer.asset_time(),
+        );
+
+        if let Err(err) = result {
+            let content = self.render_failed_page(file_name, err)?;
+            let html = match self.opt.format {
+                OutputFormat::Html if self.opt.fragment => content,
+                OutputFormat::Html => templates::page::render(
                     title,
-                    output_file
-                        .strip_prefix(&output_dir)?
-                        .to_string_lossy()
-                        .to_string(),
-                    page.level(),
-                ))
+                    &content,
+                    &BTreeMap::new(),
+                    self.color.as_ref().map(css_color),
+                    PageMeta::default(),
+                )?,
+                format => {
+                    let section = section_dir
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy();
+                    let info = PageInfo {
+                        title,
+                        section: &section,
+                        tags: Vec::new(),
+                        namespace: export::dokuwiki::namespace(&self.opt, section_dir),
+                        path: export::section_path(&self.opt, section_dir),
+                    };
+
+                    export::render(format, &info, &content)
+                }
+            };
+
+            let page_dir = export::page_dir(&self.opt, section_dir);
+            let file = export::page_file_name(self.opt.format, file_name);
+
+            fs::create_dir_all(&page_dir)?;
+            fs::write(page_dir.join(file), html)?;
+        }
+
+        if self.opt.text {
+            let text_file = sanitize_filename::sanitize(file_name.to_string() + ".txt");
+            let text = page::with_text_rules(&page::text::page_text(page), &self.name);
+            fs::write(section_dir.join(text_file), text)?;
+        }
+
+        Ok(())
+    }
+
+    /// Render all pages of the section into a single HTML file.
+    fn render_merged(&mut self, section: &Section, output_dir: &Path) -> Result<PathBuf> {
+        let entries = self.render_merged_pages(
+            section,
+            output_dir,
+            output_dir,
+            0,
+            &mut merged::Anchors::default(),
+        )?;
+
+        let html = merged::render(
+            section.display_name(),
+            &entries,
+            self.color.as_ref().map(css_color),
+        )?;
+        let file = output_dir.join(format!("{}.html", file_stem(section.display_name())));
+        fs::write(file, html)?;
+
+        Ok(output_dir.join(file_stem(section.display_name())))
+    }
+
+    /// Render the pages of the section as entries of a merged document that
+    /// is stored in `base_dir`. The section's files are stored in `output_dir`.
+    /// `anchors` holds the anchors already used in the document.
+    pub(crate) fn render_merged_pages(
+        &mut self,
+        section: &Section,
+        output_dir: &Path,
+        base_dir: &Path,
+        level: usize,
+        anchors: &mut merged::Anchors,
+    ) -> Result<Vec<merged::Entry>> {
+        self.name = section.display_name().to_string();
+        self.color = section.color().map(prepare_color);
+
+        let section_dir = self.section_dir(section, output_dir)?;
+        let asset_prefix = section_dir.strip_prefix(base_dir)?.to_string_lossy() + "/";
+
+        let mut entries = Vec::new();
+
+        let pages = self.pages(section)?;
+        let total = pages.len();
+        progress::section_started(section.display_name(), total);
+
+        for (i, (page, title, file_name, appended)) in pages.into_iter().enumerate() {
+            let anchor = anchors.unique(slugify(&format!(
+                "{} {}",
+                section.display_name(),
+                file_name
+            )));
+
+            let mut renderer = page::Renderer::new(section_dir.clone(), self);
+            renderer.set_asset_prefix(asset_prefix.to_string());
+            renderer.set_anchors(mem::take(anchors));
+
+            let scope = format!("[id=\"{}\"]", anchor);
+            let fragment = catch_panic(|| renderer.render_page_fragment(page, &appended, &scope));
+            *anchors = renderer.take_anchors();
+
+            let (content, styles) = match fragment {
+                Ok(fragment) => fragment,
+                Err(err) => (self.render_failed_page(&file_name, err)?, Vec::new()),
+            };
+
+            progress::page_converted(section.display_name(), &title, i, total);
+
+            entries.push(merged::Entry {
+                title,
+                anchor,
+                level: level + page.level().max(1) as usize - 1,
+                content,
+                styles,
+            });
+        }
+
+        self.finish_assets()?;
+        self.write_asset_manifest(&section_dir)?;
+
+        Ok(entries)
+    }
+
+    /// Render the placeholder content for a page that failed to render and
+    /// record the failure, or return the error if `--keep-going` isn't set.
+    fn render_failed_page(&mut self, file_name: &str, err: Report) -> Result<String> {
+        if !self.opt.keep_going {
+            return Err(err);
+        }
+
+        let message = format!("{:#}", err);
+        warn!(target: status::SKIPPED, "Failed to render page {}: {}", file_name, message);
+        status::page_failed();
+
+        let content = format!(
+            "<div class=\"render-error\"><p>{}</p><pre>{}</pre></div>",
+            strings().page_failed,
+            templates::html_escape(&message)?
+        );
+        self.failed.insert(file_name.to_string(), message);
+
+        Ok(content)
+    }
+
+    /// Wait until the assets queued by the pages have been written.
+    fn finish_assets(&mut self) -> Result<()> {
+        match self.asset_writer.take() {
+            Some(writer) => writer.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait until the assets queued so far have been written, e.g. before a
+    /// written page is passed to a command, and keep queueing assets.
+    pub(crate) fn flush_assets(&mut self) -> Result<()> {
+        if let Some(writer) = self.asset_writer.take() {
+            writer.finish()?;
+            self.asset_writer = Some(AssetWriter::new(&self.opt, self.opt.asset_threads));
+        }
+
+        Ok(())
+    }
+
+    /// Reserve a file name in the section's directory for the page that's
+    /// being rendered. Returns `false` if the name is already taken.
+    pub(crate) fn reserve_file(&mut self, name: &str) -> bool {
+        if !self.files.insert(name.to_string()) {
+            return false;
+        }
+
+        self.page_files.push(name.to_string());
+
+        true
+    }
+
+    /// Remember the word count and reading time of a page for the manifest.
+    pub(crate) fn record_word_count(&mut self, file_name: &str, words: usize, minutes: usize) {
+        self.word_counts
+            .insert(file_name.to_string(), (words, minutes));
+    }
+
+    /// Remember the number of redactions on a page for the manifest.
+    pub(crate) fn record_redactions(&mut self, file_name: &str, count: usize) {
+        self.redactions.insert(file_name.to_string(), count);
+    }
+
+    /// Write the mapping of hashed asset file names to their original names,
+    /// along with the pages that failed to render, the word counts of the
+    /// rendered pages and the number of redactions per page.
+    fn write_asset_manifest(&self, section_dir: &Path) -> Result<()> {
+        if self.assets.is_empty()
+            && self.failed.is_empty()
+            && self.word_counts.is_empty()
+            && self.redactions.is_empty()
+        {
+            return Ok(());
+        }
+
+        let mut manifest: toml::value::Table = self
+            .assets
+            .iter()
+            .map(|(name, original)| (name.clone(), toml::Value::String(original.clone())))
+            .collect();
+
+        if !self.failed.is_empty() {
+            let failed = self
+                .failed
+                .iter()
+                .map(|(page, error)| (page.clone(), toml::Value::String(error.clone())))
+                .collect();
+
+            manifest.insert("failed_pages".to_string(), toml::Value::Table(failed));
+        }
+
+        if !self.word_counts.is_empty() {
+            let pages = self
+                .word_counts
+                .iter()
+                .map(|(page, (words, minutes))| {
+                    let mut stats = toml::value::Table::new();
+                    stats.insert("words".to_string(), toml::Value::Integer(*words as i64));
+                    stats.insert(
+                        "reading_time".to_string(),
+                        toml::Value::Integer(*minutes as i64),
+                    );
+
+                    (page.clone(), toml::Value::Table(stats))
+                })
+                .collect();
+
+            manifest.insert("pages".to_string(), toml::Value::Table(pages));
+        }
+
+        if !self.redactions.is_empty() {
+            let redactions = self
+                .redactions
+                .iter()
+                .map(|(page, count)| (page.clone(), toml::Value::Integer(*count as i64)))
+                .collect();
+
+            manifest.insert("redactions".to_string(), toml::Value::Table(redactions));
+        }
+
+        let manifest = toml::to_string(&manifest).wrap_err("Failed to serialize asset manifest")?;
+        fs::write(section_dir.join("manifest.toml"), manifest)?;
+
+        Ok(())
+    }
+
+    /// Collect the pages to render along with their titles, file names and
+    /// the conflict pages that are appended to them.
+    fn pages<'s>(&mut self, section: &'s Section) -> Result<Vec<SectionPage<'s>>> {
+        let mut pages: Vec<SectionPage> = Vec::new();
+        let mut previous_included = false;
+        let mut fallback_title_index = 0;
+        let mut parents: Vec<(i32, String)> = Vec::new();
+
+        let all_pages = section
+            .page_series()
+            .iter()
+            .flat_map(|page_series| page_series.pages());
+
+        for (index, page) in all_pages.enumerate() {
+            match self.opt.conflicts {
+                Some(ConflictMode::Skip) if is_conflict_page(page) => continue,
+                Some(ConflictMode::Append) if is_conflict_page(page) => {
+                    if let Some(previous) = pages.last_mut().filter(|_| previous_included) {
+                        previous.3.push(page);
+                    }
+
+                    continue;
+                }
+                _ => {}
             }
+
+            let original_title = page.title_text().map(|s| s.to_string()).unwrap_or_else(|| {
+                fallback_title_index += 1;
+
+                format!("{} {}", strings().untitled_page, fallback_title_index)
+            });
+
+            // Titles end up in file names, indexes and the navigation
+            let title = page::with_text_rules(&original_title, &self.name);
+
+            let file_name = title.trim().replace("/", "_");
+
+            while matches!(parents.last(), Some((level, _)) if *level >= page.level()) {
+                parents.pop();
+            }
+
+            parents.push((page.level(), file_name.clone()));
+
+            let file_name = if let Some(template) = &self.opt.filename_template {
+                template.render(&[
+                    ("title", title.trim().to_string()),
+                    ("section", section.display_name().to_string()),
+                    ("index", (index + 1).to_string()),
+                    ("level", page.level().to_string()),
+                    ("path", parents.iter().map(|(_, name)| name).join(" - ")),
+                ])
+            } else if self.opt.subpage_prefix {
+                parents.iter().map(|(_, name)| name).join(" - ")
+            } else {
+                file_name
+            };
+
+            if let Some(filter) = &self.opt.page {
+                if original_title.trim() != filter.trim() {
+                    previous_included = false;
+
+                    continue;
+                }
+            }
+
+            let file_name = self.determine_page_filename(&file_name)?;
+
+            status::page_matched();
+            pages.push((page, title, file_name, Vec::new()));
+            previous_included = true;
+        }
+
+        Ok(pages)
+    }
+
+    /// The `onenote:` link to the page titled `page_title`, if enabled.
+    pub(crate) fn onenote_link(&self, page_title: &str) -> Option<String> {
+        if !self.opt.onenote_links || self.opt.strip_metadata {
+            return None;
         }
 
-        let toc_html = templates::section::render(section.display_name(), toc)?;
-        let toc_file = output_dir.join(format!("{}.html", section.display_name()));
-        fs::write(toc_file, toc_html)?;
+        self.source
+            .as_deref()
+            .map(|path| onenote_link(path, page_title))
+    }
+
+    fn section_dir(&self, section: &Section, output_dir: &Path) -> Result<PathBuf> {
+        let section_dir = output_dir.join(file_stem(section.display_name()));
+
+        if !section_dir.is_dir() {
+            fs::create_dir(&section_dir)?;
+        }
 
         Ok(section_dir)
     }
@@ -70,9 +617,11 @@ impl Renderer {
         let mut current_filename = sanitize_filename::sanitize(filename);
 
         loop {
-            if !self.pages.contains(&current_filename) {
-                self.pages.insert(current_filename.clone());
-
+            // Names can map to the same file, e.g. DokuWiki page IDs
+            if self
+                .pages
+                .insert(export::page_file_name(self.opt.format, &current_filename))
+            {
                 return Ok(current_filename);
             }
 
@@ -82,3 +631,62 @@ impl Renderer {
         }
     }
 }
+
+/// The name of a section's directory and of its index page without the
+/// extension. `index` is reserved for the index pages of notebooks and
+/// section groups.
+pub(crate) fn file_stem(name: &str) -> String {
+    let stem = sanitize_filename::sanitize(name);
+
+    if stem.eq_ignore_ascii_case("index") {
+        format!("{}_1", stem)
+    } else {
+        stem
+    }
+}
+
+/// Run `render`, turning panics of the renderer, e.g. on content it doesn't
+/// support, into errors so they can be handled like other render errors.
+fn catch_panic<T>(render: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(render)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+
+        Err(eyre!("Renderer panicked: {}", message))
+    })
+}
+
+/// Whether the parser failed because the section's contents can't be read,
+/// e.g. because the section is password-protected. The parser reports these
+/// with its `Malformed…` error kinds, which distinguishes them from IO errors
+/// and from files that aren't sections at all.
+pub(crate) fn is_unreadable(err: &ParserError) -> bool {
+    if std::error::Error::source(err).map_or(false, |source| source.is::<std::io::Error>()) {
+        return false;
+    }
+
+    // The parser keeps the error kind private, but its debug output names the
+    // kind's variant, e.g. `Error { kind: MalformedOneStoreData(..) }`, which
+    // doesn't change with the wording of the message
+    let debug = format!("{:?}", err);
+    let kind = debug
+        .split("kind: ")
+        .nth(1)
+        .and_then(|kind| kind.split(|c: char| !c.is_alphanumeric()).next())
+        .unwrap_or_default();
+
+    kind.starts_with("Malformed")
+}
+
+/// The start of the titles OneNote gives to pages with conflicting changes.
+const CONFLICT_TITLE: &str = "Conflicting changes";
+
+/// Whether the page holds changes that OneNote couldn't merge when syncing.
+fn is_conflict_page(page: &Page) -> bool {
+    page.title_text()
+        .map(|title| title.trim_start().starts_with(CONFLICT_TITLE))
+        .unwrap_or(false)
+}
@@ -0,0 +1,280 @@
+use crate::templates::html_escape;
+use crate::utils::url_path;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use console::style;
+use log::warn;
+use percent_encoding::percent_decode_str;
+use sha1::Sha1;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+
+/// The path of the WebSocket that live reload clients connect to.
+const LIVE_RELOAD_PATH: &str = "/.one2html/live-reload";
+
+/// The script added to HTML pages to reload them when notified.
+const LIVE_RELOAD_SCRIPT: &str = "<script>new WebSocket((location.protocol === 'https:' ? 'wss://' : 'ws://') + location.host + '/.one2html/live-reload').onmessage = () => location.reload();</script>";
+
+/// A preview server running in the background.
+pub(crate) struct Server {
+    listener: JoinHandle<()>,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Server {
+    /// Tell all connected pages to reload.
+    pub(crate) fn reload(&self) {
+        let mut clients = self.clients.lock().expect("live reload lock poisoned");
+
+        // An unmasked text frame with the payload `reload`
+        clients.retain(|mut client| client.write_all(b"\x81\x06reload").is_ok());
+    }
+
+    /// Wait until the server stops, i.e. until the process is stopped.
+    pub(crate) fn wait(self) -> Result<()> {
+        self.listener
+            .join()
+            .map_err(|_| eyre!("Preview server thread panicked"))
+    }
+}
+
+/// Start serving the files in `output_dir` over HTTP at `addr`. With
+/// `live_reload`, HTML pages reload when [`Server::reload`] is called.
+pub(crate) fn start(output_dir: &Path, addr: SocketAddr, live_reload: bool) -> Result<Server> {
+    let listener = TcpListener::bind(addr).wrap_err("Failed to start the preview server")?;
+
+    println!(
+        "Serving {} at {}",
+        style(output_dir.display()).bright(),
+        style(format!("http://{}/", addr)).bright()
+    );
+
+    let root = output_dir.to_path_buf();
+    let clients = Arc::new(Mutex::new(Vec::new()));
+    let server_clients = clients.clone();
+
+    let listener = thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("Failed to accept connection: {}", err);
+                    continue;
+                }
+            };
+            let root = root.clone();
+            let clients = Some(server_clients.clone()).filter(|_| live_reload);
+
+            thread::spawn(move || {
+                if let Err(err) = handle(stream, &root, clients) {
+                    warn!("Failed to handle request: {:#}", err);
+                }
+            });
+        }
+    });
+
+    Ok(Server { listener, clients })
+}
+
+fn handle(
+    mut stream: TcpStream,
+    root: &Path,
+    clients: Option<Arc<Mutex<Vec<TcpStream>>>>,
+) -> Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut websocket_key = None;
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            }
+        }
+
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+
+    if method != "GET" && method != "HEAD" {
+        return respond(&mut stream, "405 Method Not Allowed", "", b"", false);
+    }
+
+    let path = target
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+    let path = percent_decode_str(path).decode_utf8_lossy();
+    let live_reload = clients.is_some();
+
+    if path == LIVE_RELOAD_PATH {
+        if let Some((clients, key)) = clients.zip(websocket_key) {
+            let headers = format!(
+                "Upgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                websocket_accept(&key)
+            );
+            write!(stream, "HTTP/1.1 101 Switching Protocols\r\n{}", headers)?;
+
+            clients
+                .lock()
+                .expect("live reload lock poisoned")
+                .push(stream);
+
+            return Ok(());
+        }
+    }
+
+    let file = match resolve(root, &path) {
+        Some(file) => file,
+        None => {
+            let headers = content_type("text/plain");
+            return respond(&mut stream, "404 Not Found", &headers, b"Not found", false);
+        }
+    };
+
+    let head_only = method == "HEAD";
+
+    if file.is_dir() {
+        if !path.ends_with('/') {
+            let location = format!("Location: {}/\r\n", target);
+            return respond(&mut stream, "301 Moved Permanently", &location, b"", false);
+        }
+
+        let headers = content_type("text/html; charset=utf-8");
+        let index = file.join("index.html");
+
+        let mut data = if index.is_file() {
+            fs::read(index)?
+        } else {
+            directory_listing(&file, &path)?.into_bytes()
+        };
+
+        if live_reload {
+            inject_live_reload(&mut data);
+        }
+
+        return respond(&mut stream, "200 OK", &headers, &data, head_only);
+    }
+
+    let mut data = fs::read(&file)?;
+    let mime = mime_guess::from_path(&file).first_or_octet_stream();
+
+    if live_reload && mime == mime_guess::mime::TEXT_HTML {
+        inject_live_reload(&mut data);
+    }
+
+    let headers = content_type(mime.as_ref());
+
+    respond(&mut stream, "200 OK", &headers, &data, head_only)
+}
+
+/// Add the live reload script to an HTML page, before the closing `</body>`
+/// tag if there is one.
+fn inject_live_reload(html: &mut Vec<u8>) {
+    let position = html
+        .windows(7)
+        .rposition(|window| window == b"</body>")
+        .unwrap_or_else(|| html.len());
+
+    html.splice(position..position, LIVE_RELOAD_SCRIPT.bytes());
+}
+
+/// Calculate the `Sec-WebSocket-Accept` header for a WebSocket handshake.
+fn websocket_accept(key: &str) -> String {
+    let digest = Sha1::from(format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key)).digest();
+
+    base64::encode(digest.bytes())
+}
+
+/// Map a request path to a file in `root`, rejecting paths that would leave it.
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let mut file = root.to_path_buf();
+
+    for component in Path::new(path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(segment) => file.push(segment),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    if file.exists() {
+        Some(file)
+    } else {
+        None
+    }
+}
+
+fn directory_listing(dir: &Path, path: &str) -> Result<String> {
+    let mut names = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if entry.path().is_dir() {
+                name + "/"
+            } else {
+                name
+            }
+        })
+        .filter(|name| !name.starts_with('.'))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{0}</title></head>\n<body>\n<h1>{0}</h1>\n<ul>\n",
+        html_escape(path)?
+    );
+
+    for name in names {
+        let href = match name.strip_suffix('/') {
+            Some(dir) => url_path(Path::new(dir)) + "/",
+            None => url_path(Path::new(&name)),
+        };
+
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>\n",
+            href,
+            html_escape(&name)?
+        ));
+    }
+
+    html.push_str("</ul>\n</body>\n</html>\n");
+
+    Ok(html)
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &str,
+    body: &[u8],
+    head_only: bool,
+) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        headers,
+        body.len()
+    )?;
+
+    if !head_only {
+        stream.write_all(body)?;
+    }
+
+    Ok(())
+}
+
+fn content_type(content_type: &str) -> String {
+    format!("Content-Type: {}\r\n", content_type)
+}
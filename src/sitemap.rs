@@ -0,0 +1,53 @@
+use crate::templates::html_escape;
+use crate::utils::url_path;
+use color_eyre::eyre::{Result, WrapErr};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Write a `sitemap.xml` file that lists all HTML files in the output directory.
+pub(crate) fn write(output_dir: &Path, base_url: &str) -> Result<()> {
+    let mut files = Vec::new();
+    collect_html_files(output_dir, &mut files)?;
+    files.sort();
+
+    let mut sitemap = String::new();
+    sitemap.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    sitemap.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+    for file in files {
+        let url = base_url.to_string() + &url_path(file.strip_prefix(output_dir)?);
+
+        sitemap.push_str(&format!("  <url><loc>{}</loc></url>\n", html_escape(&url)?));
+    }
+
+    sitemap.push_str("</urlset>\n");
+
+    fs::write(output_dir.join("sitemap.xml"), sitemap).wrap_err("Failed to write sitemap")
+}
+
+/// Write a `robots.txt` file that asks all crawlers not to crawl any files.
+pub(crate) fn write_robots_txt(output_dir: &Path) -> Result<()> {
+    fs::write(
+        output_dir.join("robots.txt"),
+        "User-agent: *\nDisallow: /\n",
+    )
+    .wrap_err("Failed to write robots.txt")
+}
+
+fn collect_html_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_html_files(&path, files)?;
+        } else if path
+            .extension()
+            .map(|ext| ext == "html")
+            .unwrap_or_default()
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use crate::page::text::{page_text, word_count};
+use crate::templates;
+use crate::utils::json_string;
+use color_eyre::eyre::{Result, WrapErr};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use onenote_parser::contents::{Content, NoteTag, OutlineElement, OutlineItem};
+use onenote_parser::page::{Page, PageContent};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+static SECTIONS: Lazy<Mutex<Vec<SectionStats>>> = Lazy::new(Default::default);
+
+/// The statistics of a converted section.
+pub(crate) struct SectionStats {
+    pub(crate) name: String,
+    pub(crate) pages: Vec<PageStats>,
+}
+
+/// The statistics of a single page.
+#[derive(Default)]
+pub(crate) struct PageStats {
+    pub(crate) title: String,
+    pub(crate) words: usize,
+    pub(crate) images: usize,
+    pub(crate) attachments: usize,
+    pub(crate) attachment_size: usize,
+    pub(crate) tags: BTreeMap<String, usize>,
+}
+
+impl SectionStats {
+    /// Sum up the statistics of all pages in the section.
+    pub(crate) fn total(&self) -> PageStats {
+        let mut total = PageStats::default();
+
+        for page in &self.pages {
+            total.add(page);
+        }
+
+        total
+    }
+}
+
+impl PageStats {
+    /// Collect the statistics of a page.
+    pub(crate) fn collect(page: &Page, title: &str) -> Self {
+        let mut stats = PageStats {
+            title: title.to_string(),
+            words: word_count(&page_text(page)),
+            ..PageStats::default()
+        };
+
+        for content in page.contents() {
+            match content {
+                PageContent::Outline(outline) => stats.add_outline_items(outline.items()),
+                PageContent::Image(image) => {
+                    stats.images += 1;
+                    stats.add_note_tags(image.note_tags());
+                }
+                PageContent::EmbeddedFile(file) => {
+                    stats.attachments += 1;
+                    stats.attachment_size += file.data().len();
+                    stats.add_note_tags(file.note_tags());
+                }
+                PageContent::Ink(_) | PageContent::Unknown => {}
+            }
+        }
+
+        stats
+    }
+
+    pub(crate) fn add(&mut self, other: &PageStats) {
+        self.words += other.words;
+        self.images += other.images;
+        self.attachments += other.attachments;
+        self.attachment_size += other.attachment_size;
+
+        for (label, count) in &other.tags {
+            *self.tags.entry(label.clone()).or_default() += count;
+        }
+    }
+
+    /// The total number of note tags.
+    pub(crate) fn tag_count(&self) -> usize {
+        self.tags.values().sum()
+    }
+
+    fn add_outline_items(&mut self, items: &[OutlineItem]) {
+        for item in items {
+            match item {
+                OutlineItem::Element(element) => self.add_outline_element(element),
+                OutlineItem::Group(group) => self.add_outline_items(group.outlines()),
+            }
+        }
+    }
+
+    fn add_outline_element(&mut self, element: &OutlineElement) {
+        for content in element.contents() {
+            match content {
+                Content::RichText(text) => self.add_note_tags(text.note_tags()),
+                Content::Image(image) => {
+                    self.images += 1;
+                    self.add_note_tags(image.note_tags());
+                }
+                Content::EmbeddedFile(file) => {
+                    self.attachments += 1;
+                    self.attachment_size += file.data().len();
+                    self.add_note_tags(file.note_tags());
+                }
+                Content::Table(table) => {
+                    self.add_note_tags(table.note_tags());
+
+                    for cell in table.contents().iter().flat_map(|row| row.contents()) {
+                        for element in cell.contents() {
+                            self.add_outline_element(element);
+                        }
+                    }
+                }
+                Content::Ink(_) | Content::Unknown => {}
+            }
+        }
+
+        self.add_outline_items(element.children());
+    }
+
+    fn add_note_tags(&mut self, note_tags: &[NoteTag]) {
+        for definition in note_tags.iter().filter_map(|tag| tag.definition()) {
+            let label = definition.label();
+
+            if !label.is_empty() {
+                *self.tags.entry(label.to_string()).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// Remember the statistics of a converted section.
+pub(crate) fn record_section(section: SectionStats) {
+    let mut sections = SECTIONS.lock().expect("statistics lock poisoned");
+
+    sections.push(section);
+}
+
+/// Forget the recorded sections, e.g. before converting the inputs again.
+pub(crate) fn clear() {
+    SECTIONS.lock().expect("statistics lock poisoned").clear();
+}
+
+/// Write a `stats.html` summary and a `stats.json` file with the statistics
+/// of all converted sections.
+pub(crate) fn write(output_dir: &Path) -> Result<()> {
+    let sections = SECTIONS.lock().expect("statistics lock poisoned");
+
+    let json = format!(
+        "{{\"sections\":[{}]}}\n",
+        sections.iter().map(render_section).join(",")
+    );
+    fs::write(output_dir.join("stats.json"), json).wrap_err("Failed to write statistics file")?;
+
+    let html = templates::stats::render(&sections)?;
+    fs::write(output_dir.join("stats.html"), html).wrap_err("Failed to write statistics page")
+}
+
+fn render_section(section: &SectionStats) -> String {
+    let mut fields = vec![("name", json_string(&section.name))];
+    fields.extend(render_stats(&section.total()));
+    fields.push((
+        "pages",
+        format!("[{}]", section.pages.iter().map(render_page).join(",")),
+    ));
+
+    render_object(&fields)
+}
+
+fn render_page(page: &PageStats) -> String {
+    let mut fields = vec![("title", json_string(&page.title))];
+    fields.extend(render_stats(page));
+
+    render_object(&fields)
+}
+
+fn render_stats(stats: &PageStats) -> Vec<(&'static str, String)> {
+    vec![
+        ("words", stats.words.to_string()),
+        ("images", stats.images.to_string()),
+        ("attachments", stats.attachments.to_string()),
+        ("attachment_size", stats.attachment_size.to_string()),
+        (
+            "tags",
+            render_object(
+                &stats
+                    .tags
+                    .iter()
+                    .map(|(label, count)| (label.as_str(), count.to_string()))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+    ]
+}
+
+fn render_object(fields: &[(&str, String)]) -> String {
+    format!(
+        "{{{}}}",
+        fields
+            .iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), value))
+            .join(",")
+    )
+}
@@ -0,0 +1,61 @@
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The conversion finished without errors.
+pub(crate) const EXIT_SUCCESS: i32 = 0;
+/// The conversion failed with an error that doesn't fit the other classes.
+pub(crate) const EXIT_ERROR: i32 = 1;
+/// An input file couldn't be parsed.
+pub(crate) const EXIT_PARSE_ERROR: i32 = 2;
+/// Reading or writing a file failed.
+pub(crate) const EXIT_IO_ERROR: i32 = 3;
+/// The conversion finished, but some pages or sections have been skipped
+/// because they couldn't be converted.
+pub(crate) const EXIT_PARTIAL: i32 = 4;
+/// No page matched the `--page` filter.
+pub(crate) const EXIT_NO_MATCH: i32 = 5;
+
+/// Log target of warnings about skipped pages and sections.
+pub(crate) const SKIPPED: &str = "one2html::skipped";
+/// Log target of the warning that no page matched the `--page` filter.
+pub(crate) const NO_MATCH: &str = "one2html::no_match";
+
+static MATCHED_PAGES: AtomicUsize = AtomicUsize::new(0);
+static FAILED_PAGES: AtomicUsize = AtomicUsize::new(0);
+static CURRENT_PAGE: Lazy<Mutex<Option<String>>> = Lazy::new(Default::default);
+
+/// Record that a page is going to be converted.
+pub(crate) fn page_matched() {
+    MATCHED_PAGES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record that `count` pages of an unchanged input would have been converted.
+pub(crate) fn pages_matched(count: usize) {
+    MATCHED_PAGES.fetch_add(count, Ordering::Relaxed);
+}
+
+/// Record that a page has been replaced by a placeholder because it couldn't
+/// be rendered.
+pub(crate) fn page_failed() {
+    FAILED_PAGES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn matched_pages() -> usize {
+    MATCHED_PAGES.load(Ordering::Relaxed)
+}
+
+pub(crate) fn failed_pages() -> usize {
+    FAILED_PAGES.load(Ordering::Relaxed)
+}
+
+/// Record the title of the page that is being rendered, or `None` once it
+/// has been rendered.
+pub(crate) fn set_current_page(title: Option<&str>) {
+    *CURRENT_PAGE.lock().unwrap() = title.map(str::to_string);
+}
+
+/// The title of the page that is being rendered (if any).
+pub(crate) fn current_page() -> Option<String> {
+    CURRENT_PAGE.lock().unwrap().clone()
+}
@@ -0,0 +1,165 @@
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+static STRINGS: OnceCell<Strings> = OnceCell::new();
+
+/// The user-visible strings used in the generated pages.
+#[derive(Debug, Clone)]
+pub(crate) struct Strings {
+    pub(crate) lang: String,
+    pub(crate) untitled_page: String,
+    pub(crate) previous_part: String,
+    pub(crate) next_part: String,
+    pub(crate) asset_omitted: String,
+    pub(crate) open_in_onenote: String,
+    pub(crate) conflicts: String,
+    pub(crate) deleted_pages: String,
+    pub(crate) page_failed: String,
+    pub(crate) original_email: String,
+    pub(crate) added_pages: String,
+    pub(crate) removed_pages: String,
+    pub(crate) changed_pages: String,
+    pub(crate) statistics: String,
+    pub(crate) total: String,
+    pub(crate) words: String,
+    pub(crate) images: String,
+    pub(crate) attachments: String,
+    pub(crate) note_tags: String,
+    pub(crate) attachment_size: String,
+    pub(crate) tag_usage: String,
+    pub(crate) pages: String,
+    pub(crate) reading_time: String,
+    pub(crate) notebooks: String,
+}
+
+impl Strings {
+    fn english() -> Self {
+        Strings {
+            lang: "en".to_string(),
+            untitled_page: "Untitled Page".to_string(),
+            previous_part: "Previous part".to_string(),
+            next_part: "Next part".to_string(),
+            asset_omitted: "This file has not been exported".to_string(),
+            open_in_onenote: "Open in OneNote".to_string(),
+            conflicts: "Conflicts".to_string(),
+            deleted_pages: "Deleted Pages".to_string(),
+            page_failed: "This page could not be converted".to_string(),
+            original_email: "Original email".to_string(),
+            added_pages: "Added pages".to_string(),
+            removed_pages: "Removed pages".to_string(),
+            changed_pages: "Changed pages".to_string(),
+            statistics: "Statistics".to_string(),
+            total: "Total".to_string(),
+            words: "Words".to_string(),
+            images: "Images".to_string(),
+            attachments: "Attachments".to_string(),
+            note_tags: "Tags".to_string(),
+            attachment_size: "Attachment size".to_string(),
+            tag_usage: "Tag usage".to_string(),
+            pages: "Pages".to_string(),
+            reading_time: "{words} words · {minutes} min read".to_string(),
+            notebooks: "Notebooks".to_string(),
+        }
+    }
+
+    fn german() -> Self {
+        Strings {
+            lang: "de".to_string(),
+            untitled_page: "Unbenannte Seite".to_string(),
+            previous_part: "Vorheriger Teil".to_string(),
+            next_part: "Nächster Teil".to_string(),
+            asset_omitted: "Diese Datei wurde nicht exportiert".to_string(),
+            open_in_onenote: "In OneNote öffnen".to_string(),
+            conflicts: "Konflikte".to_string(),
+            deleted_pages: "Gelöschte Seiten".to_string(),
+            page_failed: "Diese Seite konnte nicht konvertiert werden".to_string(),
+            original_email: "Original-E-Mail".to_string(),
+            added_pages: "Hinzugefügte Seiten".to_string(),
+            removed_pages: "Entfernte Seiten".to_string(),
+            changed_pages: "Geänderte Seiten".to_string(),
+            statistics: "Statistik".to_string(),
+            total: "Gesamt".to_string(),
+            words: "Wörter".to_string(),
+            images: "Bilder".to_string(),
+            attachments: "Anhänge".to_string(),
+            note_tags: "Markierungen".to_string(),
+            attachment_size: "Größe der Anhänge".to_string(),
+            tag_usage: "Verwendete Markierungen".to_string(),
+            pages: "Seiten".to_string(),
+            reading_time: "{words} Wörter · {minutes} Min. Lesezeit".to_string(),
+            notebooks: "Notizbücher".to_string(),
+        }
+    }
+
+    fn for_language(lang: &str) -> Self {
+        match lang {
+            "de" => Strings::german(),
+            "en" => Strings::english(),
+
+            // Keep the English strings but mark the document with the requested language
+            _ => Strings {
+                lang: lang.to_string(),
+                ..Strings::english()
+            },
+        }
+    }
+
+    fn set(&mut self, key: &str, value: String) -> Result<()> {
+        match key {
+            "lang" => self.lang = value,
+            "untitled_page" => self.untitled_page = value,
+            "previous_part" => self.previous_part = value,
+            "next_part" => self.next_part = value,
+            "asset_omitted" => self.asset_omitted = value,
+            "open_in_onenote" => self.open_in_onenote = value,
+            "conflicts" => self.conflicts = value,
+            "deleted_pages" => self.deleted_pages = value,
+            "page_failed" => self.page_failed = value,
+            "original_email" => self.original_email = value,
+            "added_pages" => self.added_pages = value,
+            "removed_pages" => self.removed_pages = value,
+            "changed_pages" => self.changed_pages = value,
+            "statistics" => self.statistics = value,
+            "total" => self.total = value,
+            "words" => self.words = value,
+            "images" => self.images = value,
+            "attachments" => self.attachments = value,
+            "note_tags" => self.note_tags = value,
+            "attachment_size" => self.attachment_size = value,
+            "tag_usage" => self.tag_usage = value,
+            "pages" => self.pages = value,
+            "reading_time" => self.reading_time = value,
+            "notebooks" => self.notebooks = value,
+            _ => return Err(eyre!("Unknown string: {}", key)),
+        }
+
+        Ok(())
+    }
+}
+
+/// Initialize the strings for the language `lang`, overridden by the strings
+/// in the TOML file `path` (if any).
+pub(crate) fn init(lang: &str, path: Option<&Path>) -> Result<()> {
+    let mut strings = Strings::for_language(lang);
+
+    if let Some(path) = path {
+        let data = fs::read_to_string(path).wrap_err("Failed to read strings file")?;
+        let overrides: HashMap<String, String> =
+            toml::from_str(&data).wrap_err("Failed to parse strings file")?;
+
+        for (key, value) in overrides {
+            strings.set(&key, value)?;
+        }
+    }
+
+    STRINGS
+        .set(strings)
+        .map_err(|_| eyre!("Strings are already initialized"))
+}
+
+pub(crate) fn strings() -> &'static Strings {
+    STRINGS.get_or_init(Strings::english)
+}
@@ -0,0 +1,78 @@
+use crate::cli::Theme;
+use crate::diff::Change;
+use crate::strings::strings;
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
+use askama::Template;
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+use std::collections::HashSet;
+
+#[derive(Template)]
+#[template(path = "diff.html")]
+struct DiffTemplate<'a> {
+    added: Vec<&'a str>,
+    removed: Vec<&'a str>,
+    changed: Vec<ChangedPage<'a>>,
+    added_label: &'a str,
+    removed_label: &'a str,
+    changed_label: &'a str,
+    lang: &'a str,
+    theme: Theme,
+    noindex: bool,
+}
+
+struct ChangedPage<'a> {
+    title: &'a str,
+    old_lines: Vec<Line<'a>>,
+    new_lines: Vec<Line<'a>>,
+}
+
+struct Line<'a> {
+    text: &'a str,
+    changed: bool,
+}
+
+pub(crate) fn render(changes: &[Change]) -> Result<String> {
+    let mut template = DiffTemplate {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        added_label: &strings().added_pages,
+        removed_label: &strings().removed_pages,
+        changed_label: &strings().changed_pages,
+        lang: &strings().lang,
+        theme: theme(),
+        noindex: noindex(),
+    };
+
+    for change in changes {
+        match change {
+            Change::Added { title } => template.added.push(title),
+            Change::Removed { title } => template.removed.push(title),
+            Change::Changed {
+                title,
+                old_text,
+                new_text,
+            } => template.changed.push(ChangedPage {
+                title,
+                old_lines: lines(old_text, new_text),
+                new_lines: lines(new_text, old_text),
+            }),
+        }
+    }
+
+    template.render().wrap_err("Failed to render diff template")
+}
+
+/// Split `text` into lines, marking the lines that don't occur in `other`.
+fn lines<'a>(text: &'a str, other: &str) -> Vec<Line<'a>> {
+    let other: HashSet<&str> = other.lines().collect();
+
+    text.lines()
+        .map(|line| Line {
+            text: line,
+            changed: !other.contains(line),
+        })
+        .collect()
+}
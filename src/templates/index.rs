@@ -0,0 +1,37 @@
+use crate::cli::Theme;
+use crate::strings::strings;
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
+use askama::Template;
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+
+#[derive(Template)]
+#[template(path = "index.html")]
+struct IndexTemplate<'a> {
+    title: &'a str,
+    entries: &'a [(String, String)],
+    lang: &'a str,
+    theme: Theme,
+    noindex: bool,
+}
+
+/// Render the index of all notebooks and sections given by their names and
+/// the paths of their index pages.
+pub(crate) fn render(entries: &[(String, String)]) -> Result<String> {
+    let template = IndexTemplate {
+        title: &strings().notebooks,
+        entries,
+        lang: &strings().lang,
+        theme: theme(),
+        noindex: noindex(),
+    };
+
+    template
+        .render()
+        .wrap_err("Failed to render index template")
+}
+
+mod filters {
+    pub(crate) use crate::templates::url_encode as encode;
+}
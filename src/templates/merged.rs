@@ -0,0 +1,84 @@
+use crate::cli::Theme;
+use crate::strings::strings;
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
+use crate::utils::StyleSet;
+use askama::Template;
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+#[derive(Template)]
+#[template(path = "merged.html", escape = "none")]
+struct MergedTemplate<'a> {
+    name: &'a str,
+    entries: &'a [Entry],
+    global_styles: Vec<(&'a String, &'a StyleSet)>,
+    color: Option<String>,
+    lang: &'a str,
+    theme: Theme,
+    noindex: bool,
+}
+
+/// A page (or a heading without content) in a merged document.
+#[derive(Debug)]
+pub(crate) struct Entry {
+    pub(crate) title: String,
+    pub(crate) anchor: String,
+    pub(crate) level: usize,
+    pub(crate) content: String,
+    pub(crate) styles: Vec<(String, StyleSet)>,
+}
+
+/// The anchors used in a merged document.
+#[derive(Default)]
+pub(crate) struct Anchors(HashMap<String, usize>);
+
+impl Anchors {
+    /// Make `anchor` unique within the document by numbering repeated
+    /// anchors, e.g. `notes`, `notes-2`.
+    pub(crate) fn unique(&mut self, anchor: String) -> String {
+        let mut anchor = anchor;
+
+        loop {
+            let count = self.0.entry(anchor.clone()).or_default();
+            *count += 1;
+
+            if *count == 1 {
+                return anchor;
+            }
+
+            anchor = format!("{}-{}", anchor, count);
+        }
+    }
+}
+
+impl Entry {
+    fn heading_level(&self) -> usize {
+        (self.level + 2).min(6)
+    }
+}
+
+pub(crate) fn render(name: &str, entries: &[Entry], color: Option<String>) -> Result<String> {
+    MergedTemplate {
+        name,
+        entries,
+        global_styles: entries
+            .iter()
+            .flat_map(|entry| entry.styles.iter())
+            .map(|(selector, styles)| (selector, styles))
+            .sorted_by(|(a, _), (b, _)| Ord::cmp(a, b))
+            .collect(),
+        color,
+        lang: &strings().lang,
+        theme: theme(),
+        noindex: noindex(),
+    }
+    .render()
+    .wrap_err("Failed to render merged template")
+}
+
+mod filters {
+    pub(crate) use crate::templates::html_escape as html;
+}
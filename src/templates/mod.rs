@@ -1,9 +1,15 @@
 use percent_encoding::AsciiSet;
 use std::path;
 
+pub(crate) mod diff;
+pub(crate) mod index;
+pub(crate) mod merged;
 pub(crate) mod notebook;
 pub(crate) mod page;
+pub(crate) mod robots;
 pub(crate) mod section;
+pub(crate) mod stats;
+pub(crate) mod theme;
 
 const ASCII_SET: AsciiSet = percent_encoding::NON_ALPHANUMERIC.remove(path::MAIN_SEPARATOR as u8);
 
@@ -11,3 +17,12 @@ const ASCII_SET: AsciiSet = percent_encoding::NON_ALPHANUMERIC.remove(path::MAIN
 pub(crate) fn url_encode(str: &str) -> ::askama::Result<String> {
     Ok(percent_encoding::utf8_percent_encode(&str, &ASCII_SET).to_string())
 }
+
+#[allow(clippy::unnecessary_wraps)]
+pub(crate) fn html_escape(str: &str) -> ::askama::Result<String> {
+    Ok(str
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;"))
+}
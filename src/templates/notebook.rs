@@ -1,19 +1,48 @@
+use crate::cli::Theme;
 use crate::notebook::RgbColor;
+use crate::strings::strings;
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
 use askama::Template;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
+use std::path::Path;
 
 #[derive(Template)]
 #[template(path = "notebook.html")]
 struct NotebookTemplate<'a> {
     name: &'a str,
     toc: &'a [Toc],
-    _bool: fn(&bool) -> bool,
+    favicon_dir: Option<String>,
+    lang: &'a str,
+    theme: Theme,
+    noindex: bool,
 }
 
+#[derive(Debug)]
 pub(crate) enum Toc {
     Section(Section),
-    SectionGroup(String, Vec<Section>),
+    SectionGroup(SectionGroup),
+}
+
+impl Toc {
+    /// Move the entry into the directory `dir`, `levels` levels deeper.
+    pub(crate) fn nest(self, dir: &Path, levels: usize) -> Self {
+        let prefix = |path: String| dir.join(path).to_string_lossy().to_string();
+
+        match self {
+            Toc::Section(section) => Toc::Section(Section {
+                path: prefix(section.path),
+                level: section.level + levels,
+                ..section
+            }),
+            Toc::SectionGroup(group) => Toc::SectionGroup(SectionGroup {
+                path: prefix(group.path),
+                level: group.level + levels,
+                ..group
+            }),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -21,20 +50,31 @@ pub(crate) struct Section {
     pub(crate) name: String,
     pub(crate) path: String,
     pub(crate) color: Option<RgbColor>,
+    pub(crate) level: usize,
 }
 
-pub(crate) fn render(name: &str, toc: &[Toc]) -> Result<String> {
-    let template = NotebookTemplate { name, toc, _bool };
+#[derive(Debug)]
+pub(crate) struct SectionGroup {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) level: usize,
+}
+
+pub(crate) fn render(name: &str, toc: &[Toc], favicon_dir: Option<String>) -> Result<String> {
+    let template = NotebookTemplate {
+        name,
+        toc,
+        favicon_dir,
+        lang: &strings().lang,
+        theme: theme(),
+        noindex: noindex(),
+    };
 
     template
         .render()
         .wrap_err("Failed to render notebook template")
 }
 
-fn _bool(b: &bool) -> bool {
-    *b
-}
-
 mod filters {
     pub(crate) use crate::templates::url_encode as encode;
 }
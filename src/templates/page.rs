@@ -1,9 +1,12 @@
+use crate::cli::Theme;
+use crate::strings::strings;
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
 use crate::utils::StyleSet;
 use askama::Template;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
-use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Template)]
 #[template(path = "page.html", escape = "none")]
@@ -11,21 +14,78 @@ struct PageTemplate<'a> {
     name: &'a str,
     content: &'a str,
     global_styles: Vec<(&'a String, &'a StyleSet)>,
+    color: Option<String>,
+    lang: &'a str,
+    previous_label: &'a str,
+    next_label: &'a str,
+    onenote_label: &'a str,
+    meta: PageMeta<'a>,
+    theme: Theme,
+    noindex: bool,
+}
+
+#[derive(Template)]
+#[template(path = "fragment.html", escape = "none")]
+struct FragmentTemplate<'a> {
+    content: &'a str,
+    global_styles: Vec<(&'a String, &'a StyleSet)>,
+}
+
+/// Additional information about a rendered page.
+#[derive(Debug, Default)]
+pub(crate) struct PageMeta<'a> {
+    pub(crate) previous_part: Option<&'a String>,
+    pub(crate) next_part: Option<&'a String>,
+    pub(crate) url: Option<String>,
+    pub(crate) json_ld: Option<String>,
+    pub(crate) onenote_link: Option<String>,
+    pub(crate) outline_controls: bool,
+    pub(crate) conflicts: bool,
+    pub(crate) word_count: Option<usize>,
+    pub(crate) reading_time: Option<usize>,
+    pub(crate) footer: Option<String>,
+    pub(crate) favicon_dir: Option<String>,
 }
 
 pub(crate) fn render(
     name: &str,
     content: &str,
-    global_styles: &HashMap<String, StyleSet>,
+    global_styles: &BTreeMap<String, StyleSet>,
+    color: Option<String>,
+    meta: PageMeta,
 ) -> Result<String> {
     PageTemplate {
         name,
         content,
-        global_styles: global_styles
-            .iter()
-            .sorted_by(|(a, _), (b, _)| Ord::cmp(a, b))
-            .collect(),
+        global_styles: global_styles.iter().collect(),
+        color,
+        lang: &strings().lang,
+        previous_label: &strings().previous_part,
+        next_label: &strings().next_part,
+        onenote_label: &strings().open_in_onenote,
+        meta,
+        theme: theme(),
+        noindex: noindex(),
     }
     .render()
     .wrap_err("Failed to render page template")
 }
+
+/// Render the content of a page without the surrounding document, preceded
+/// by the page's styles.
+pub(crate) fn render_fragment(
+    content: &str,
+    global_styles: &BTreeMap<String, StyleSet>,
+) -> Result<String> {
+    FragmentTemplate {
+        content,
+        global_styles: global_styles.iter().collect(),
+    }
+    .render()
+    .wrap_err("Failed to render page fragment template")
+}
+
+mod filters {
+    pub(crate) use crate::templates::html_escape as html;
+    pub(crate) use crate::templates::url_encode as encode;
+}
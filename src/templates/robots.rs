@@ -0,0 +1,13 @@
+use once_cell::sync::OnceCell;
+
+static NOINDEX: OnceCell<bool> = OnceCell::new();
+
+/// Set whether the generated pages ask search engines not to index them.
+/// The setting can only be set once.
+pub(crate) fn init(noindex: bool) {
+    let _ = NOINDEX.set(noindex);
+}
+
+pub(crate) fn noindex() -> bool {
+    NOINDEX.get().copied().unwrap_or(false)
+}
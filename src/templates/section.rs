@@ -1,3 +1,7 @@
+use crate::cli::Theme;
+use crate::strings::strings;
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
 use askama::Template;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
@@ -7,25 +11,66 @@ use color_eyre::Result;
 struct NotebookTemplate<'a> {
     name: &'a str,
     pages: Vec<Page<'a>>,
+    conflicts: Vec<Page<'a>>,
+    conflicts_label: &'a str,
+    color: Option<String>,
+    favicon_dir: Option<String>,
+    lang: &'a str,
+    theme: Theme,
+    noindex: bool,
 }
 
 struct Page<'a> {
     name: &'a str,
     path: &'a str,
-    level: i32,
+    thumbnail: Option<&'a str>,
+    has_children: bool,
+    closing_tags: String,
 }
 
-pub(crate) fn render(name: &str, pages: Vec<(String, String, i32)>) -> Result<String> {
+pub(crate) fn render(
+    name: &str,
+    pages: Vec<(String, String, i32, Option<String>)>,
+    conflicts: Vec<(String, String)>,
+    color: Option<String>,
+    favicon_dir: Option<String>,
+) -> Result<String> {
+    let depths = page_depths(&pages);
+
     let template = NotebookTemplate {
         name,
         pages: pages
             .iter()
-            .map(|(name, path, level)| Page {
+            .enumerate()
+            .map(|(i, (name, path, _, thumbnail))| {
+                let depth = depths[i];
+                let next_depth = depths.get(i + 1).copied().unwrap_or(1);
+
+                Page {
+                    name,
+                    path,
+                    thumbnail: thumbnail.as_deref(),
+                    has_children: next_depth > depth,
+                    closing_tags: "</ul></li>".repeat(depth.saturating_sub(next_depth)),
+                }
+            })
+            .collect(),
+        conflicts: conflicts
+            .iter()
+            .map(|(name, path)| Page {
                 name,
                 path,
-                level: *level,
+                thumbnail: None,
+                has_children: false,
+                closing_tags: String::new(),
             })
             .collect(),
+        conflicts_label: &strings().conflicts,
+        color,
+        favicon_dir,
+        lang: &strings().lang,
+        theme: theme(),
+        noindex: noindex(),
     };
 
     template
@@ -33,6 +78,22 @@ pub(crate) fn render(name: &str, pages: Vec<(String, String, i32)>) -> Result<St
         .wrap_err("Failed to render section template")
 }
 
+/// Calculate the nesting depth of each page in the navigation tree.
+///
+/// A page can be at most one level deeper than the page before it, even if
+/// OneNote reports a larger level (e.g. a sub-subpage without a subpage).
+fn page_depths(pages: &[(String, String, i32, Option<String>)]) -> Vec<usize> {
+    let mut depths: Vec<usize> = Vec::with_capacity(pages.len());
+
+    for (_, _, level, _) in pages {
+        let max_depth = depths.last().map(|depth| depth + 1).unwrap_or(1);
+
+        depths.push((*level).max(1).min(max_depth as i32) as usize);
+    }
+
+    depths
+}
+
 mod filters {
     pub(crate) use crate::templates::url_encode as encode;
 }
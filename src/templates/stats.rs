@@ -0,0 +1,92 @@
+use crate::cli::Theme;
+use crate::stats::{PageStats, SectionStats};
+use crate::strings::{strings, Strings};
+use crate::templates::robots::noindex;
+use crate::templates::theme::theme;
+use crate::utils::format_size;
+use askama::Template;
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+
+#[derive(Template)]
+#[template(path = "stats.html")]
+struct StatsTemplate<'a> {
+    sections: Vec<Section<'a>>,
+    total: Row<'a>,
+    tags: Vec<(String, usize)>,
+    strings: &'a Strings,
+    lang: &'a str,
+    theme: Theme,
+    noindex: bool,
+}
+
+struct Section<'a> {
+    name: &'a str,
+    pages: Vec<Row<'a>>,
+    total: Row<'a>,
+}
+
+struct Row<'a> {
+    name: &'a str,
+    pages: usize,
+    words: usize,
+    images: usize,
+    attachments: usize,
+    attachment_size: String,
+    tags: usize,
+}
+
+impl<'a> Row<'a> {
+    fn new(name: &'a str, pages: usize, stats: &PageStats) -> Self {
+        Row {
+            name,
+            pages,
+            words: stats.words,
+            images: stats.images,
+            attachments: stats.attachments,
+            attachment_size: format_size(stats.attachment_size),
+            tags: stats.tag_count(),
+        }
+    }
+}
+
+pub(crate) fn render(sections: &[SectionStats]) -> Result<String> {
+    let mut total = PageStats::default();
+    let mut page_count = 0;
+
+    let sections = sections
+        .iter()
+        .map(|section| {
+            let section_total = section.total();
+            total.add(&section_total);
+            page_count += section.pages.len();
+
+            Section {
+                name: &section.name,
+                pages: section
+                    .pages
+                    .iter()
+                    .map(|page| Row::new(&page.title, 1, page))
+                    .collect(),
+                total: Row::new(&section.name, section.pages.len(), &section_total),
+            }
+        })
+        .collect();
+
+    let mut tags: Vec<_> = total.tags.clone().into_iter().collect();
+    tags.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let template = StatsTemplate {
+        sections,
+        total: Row::new(&strings().total, page_count, &total),
+        tags,
+        strings: strings(),
+        lang: &strings().lang,
+        theme: theme(),
+        noindex: noindex(),
+    };
+
+    template
+        .render()
+        .wrap_err("Failed to render statistics template")
+}
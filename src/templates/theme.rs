@@ -0,0 +1,35 @@
+use crate::cli::Theme;
+use once_cell::sync::OnceCell;
+
+static THEME: OnceCell<Theme> = OnceCell::new();
+
+/// Set the theme of the generated pages. The theme can only be set once.
+pub(crate) fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+pub(crate) fn theme() -> Theme {
+    THEME.get().copied().unwrap_or(Theme::OneNote)
+}
+
+impl Theme {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Theme::OneNote => "onenote",
+            Theme::Minimal => "minimal",
+            Theme::Sepia => "sepia",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    /// The styles the theme adds to the pages and navigation. The `onenote`
+    /// theme uses the default styles only.
+    pub(crate) fn styles(&self) -> &'static str {
+        match self {
+            Theme::OneNote => "",
+            Theme::Minimal => include_str!("themes/minimal.css"),
+            Theme::Sepia => include_str!("themes/sepia.css"),
+            Theme::HighContrast => include_str!("themes/high-contrast.css"),
+        }
+    }
+}
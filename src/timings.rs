@@ -0,0 +1,64 @@
+use console::style;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The number of pages listed in the report.
+const SLOWEST_PAGES: usize = 10;
+
+static TIMINGS: Lazy<Mutex<Timings>> = Lazy::new(Default::default);
+
+#[derive(Default)]
+struct Timings {
+    inputs: Vec<(String, Duration)>,
+    pages: Vec<PageTiming>,
+}
+
+struct PageTiming {
+    name: String,
+    render: Duration,
+    assets: Duration,
+}
+
+/// Record the time it took to parse an input file.
+pub(crate) fn record_parse(name: &str, duration: Duration) {
+    let mut timings = TIMINGS.lock().expect("timings lock poisoned");
+
+    timings.inputs.push((name.to_string(), duration));
+}
+
+/// Record the time it took to render a page, including the time spent
+/// writing its assets.
+pub(crate) fn record_page(name: &str, render: Duration, assets: Duration) {
+    let mut timings = TIMINGS.lock().expect("timings lock poisoned");
+
+    timings.pages.push(PageTiming {
+        name: name.to_string(),
+        render,
+        assets,
+    });
+}
+
+/// Print the parse time of each input file and the slowest pages.
+pub(crate) fn print_report() {
+    let mut timings = TIMINGS.lock().expect("timings lock poisoned");
+
+    println!("Parse times:");
+
+    for (name, duration) in &timings.inputs {
+        println!("  {:>10.2?}  {}", duration, style(name).bright());
+    }
+
+    timings.pages.sort_by(|a, b| b.render.cmp(&a.render));
+
+    println!("Slowest pages (render time, asset write time):");
+
+    for page in timings.pages.iter().take(SLOWEST_PAGES) {
+        println!(
+            "  {:>10.2?}  {:>10.2?}  {}",
+            page.render,
+            page.assets,
+            style(&page.name).bright()
+        );
+    }
+}
@@ -1,11 +1,19 @@
+use crate::progress;
 use indicatif::ProgressBar;
 use itertools::Itertools;
-use std::collections::HashMap;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Display;
+use std::path::{Component, Path, Prefix};
 use std::time::Duration;
 
 pub(crate) fn with_progress<T, F: FnMut() -> T>(msg: &'static str, mut f: F) -> T {
+    if progress::is_json() {
+        return f();
+    }
+
     let bar = ProgressBar::new_spinner();
     bar.set_message(msg);
     bar.enable_steady_tick(Duration::new(16, 0).as_millis() as u64);
@@ -25,16 +33,175 @@ pub(crate) fn px(inches: f32) -> String {
     format!("{}px", (inches * 48.0).round())
 }
 
-pub(crate) struct AttributeSet(HashMap<&'static str, String>);
+const URL_PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Build a `onenote:` link that opens the page titled `page_title` in the
+/// section file at `path`.
+pub(crate) fn onenote_link(path: &Path, page_title: &str) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    let file_path = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                    Some(format!("{}:", letter as char))
+                }
+                _ => Some(prefix.as_os_str().to_string_lossy().to_string()),
+            },
+            Component::Normal(segment) => {
+                Some(utf8_percent_encode(&segment.to_string_lossy(), URL_PATH_SEGMENT).to_string())
+            }
+            _ => None,
+        })
+        .join("/");
+
+    format!(
+        "onenote:file:///{}#{}",
+        file_path,
+        utf8_percent_encode(page_title, URL_PATH_SEGMENT)
+    )
+}
+
+/// Convert a relative file path into a URL path, percent-encoding its segments.
+pub(crate) fn url_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => {
+                Some(utf8_percent_encode(&segment.to_string_lossy(), URL_PATH_SEGMENT).to_string())
+            }
+            _ => None,
+        })
+        .join("/")
+}
+
+/// Encode a string as a JSON string literal that is safe to embed in a
+/// `<script>` element.
+pub(crate) fn json_string(str: &str) -> String {
+    let mut json = String::with_capacity(str.len() + 2);
+    json.push('"');
+
+    for c in str.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '<' | '>' | '&' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+/// Name a file after its contents: the first 128 bits of the SHA-256 hash of
+/// `data` in hex, followed by the extension of `filename` if it has one.
+pub(crate) fn content_filename(filename: &str, data: &[u8]) -> String {
+    let hash = format!("{:x}", Sha256::digest(data));
+
+    match Path::new(filename).extension() {
+        Some(ext) => format!("{}.{}", &hash[..32], ext.to_string_lossy()),
+        None => hash[..32].to_string(),
+    }
+}
+
+/// Calculate the 64 bit FNV-1a hash of `data`.
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    fnv1a_update(0xcbf2_9ce4_8422_2325, data)
+}
+
+/// Continue calculating a FNV-1a `hash` with more `data`.
+pub(crate) fn fnv1a_update(hash: u64, data: &[u8]) -> u64 {
+    data.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Convert a text into a lowercase identifier that is safe to use in URLs.
+pub(crate) fn slugify(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_lowercase())
+        .join("-")
+}
+
+pub(crate) fn format_size(size: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = size as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// A CSS color that a custom stylesheet can override by setting the variable
+/// `--{prefix}-{name}`. Without a name, the color's hex code is used instead.
+pub(crate) fn css_var_color(prefix: &str, name: Option<&str>, r: u8, g: u8, b: u8) -> String {
+    let name = name
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{:02x}{:02x}{:02x}", r, g, b));
+
+    format!("var(--{}-{}, rgb({},{},{}))", prefix, name, r, g, b)
+}
+
+/// A highlight color that a custom stylesheet can override by setting the
+/// variable `--hl-{name}`, e.g. `--hl-yellow`.
+pub(crate) fn highlight_color(r: u8, g: u8, b: u8) -> String {
+    css_var_color("hl", highlight_name(r, g, b), r, g, b)
+}
+
+/// The name of one of OneNote's highlight colors.
+fn highlight_name(r: u8, g: u8, b: u8) -> Option<&'static str> {
+    match (r, g, b) {
+        (255, 255, 0) => Some("yellow"),
+        (0, 255, 0) => Some("green"),
+        (0, 255, 255) => Some("turquoise"),
+        (255, 0, 255) => Some("pink"),
+        (0, 0, 255) => Some("blue"),
+        (255, 0, 0) => Some("red"),
+        (0, 0, 128) => Some("dark-blue"),
+        (0, 128, 128) => Some("teal"),
+        (0, 128, 0) => Some("dark-green"),
+        (128, 0, 128) => Some("purple"),
+        (128, 0, 0) => Some("dark-red"),
+        (128, 128, 0) => Some("dark-yellow"),
+        (128, 128, 128) => Some("gray"),
+        (192, 192, 192) => Some("light-gray"),
+        _ => None,
+    }
+}
+
+pub(crate) struct AttributeSet(BTreeMap<&'static str, String>);
 
 impl AttributeSet {
     pub(crate) fn new() -> Self {
-        Self(HashMap::new())
+        Self(BTreeMap::new())
     }
 
     pub(crate) fn set(&mut self, attribute: &'static str, value: String) {
         self.0.insert(attribute, value);
     }
+
+    pub(crate) fn remove(&mut self, attribute: &'static str) {
+        self.0.remove(attribute);
+    }
 }
 
 impl Display for AttributeSet {
@@ -44,7 +211,6 @@ impl Display for AttributeSet {
             "{}",
             self.0
                 .iter()
-                .sorted_by(|(a, _), (b, _)| Ord::cmp(a, b))
                 .map(|(attr, value)| attr.to_string() + "=\"" + &value + "\"")
                 .join(" ")
         )
@@ -52,11 +218,11 @@ impl Display for AttributeSet {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct StyleSet(HashMap<&'static str, String>);
+pub(crate) struct StyleSet(BTreeMap<&'static str, String>);
 
 impl StyleSet {
     pub(crate) fn new() -> Self {
-        Self(HashMap::new())
+        Self(BTreeMap::new())
     }
 
     pub(crate) fn set(&mut self, prop: &'static str, value: String) {
@@ -67,6 +233,14 @@ impl StyleSet {
         self.0.extend(other.0.into_iter())
     }
 
+    pub(crate) fn get(&self, prop: &str) -> Option<&str> {
+        self.0.get(prop).map(|value| value.as_str())
+    }
+
+    pub(crate) fn remove(&mut self, prop: &str) {
+        self.0.remove(prop);
+    }
+
     pub(crate) fn len(&self) -> usize {
         self.0.len()
     }
@@ -79,7 +253,6 @@ impl Display for StyleSet {
             "{}",
             self.0
                 .iter()
-                .sorted_by(|(a, _), (b, _)| Ord::cmp(a, b))
                 .map(|(attr, value)| attr.to_string() + ": " + &value + ";")
                 .join(" ")
         )
@@ -0,0 +1,76 @@
+use crate::cache;
+use crate::download;
+use color_eyre::eyre::{ContextCompat, Result};
+use console::style;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the input files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watch the input files for changes and call `on_change` once they have
+/// settled, until the process is stopped. Inputs given as URLs aren't watched.
+pub(crate) fn watch(inputs: &[PathBuf], mut on_change: impl FnMut()) -> Result<()> {
+    println!("Watching the input files for changes...");
+
+    let mut last = modification_times(inputs)?;
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = modification_times(inputs)?;
+
+        if current == last {
+            continue;
+        }
+
+        // Wait until the files stop changing, e.g. while they are synced
+        last = current;
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let current = modification_times(inputs)?;
+
+            if current == last {
+                break;
+            }
+
+            last = current;
+        }
+
+        println!("{}", style("Input files changed, converting...").bright());
+        on_change();
+    }
+}
+
+fn modification_times(inputs: &[PathBuf]) -> Result<BTreeMap<PathBuf, Option<SystemTime>>> {
+    let mut times = BTreeMap::new();
+
+    for input in inputs.iter().filter(|input| download::url(input).is_none()) {
+        for file in input_files(input)? {
+            let time = fs::metadata(&file).and_then(|meta| meta.modified()).ok();
+
+            times.insert(file, time);
+        }
+    }
+
+    Ok(times)
+}
+
+/// The files that make up an input: the section file, or all files of a
+/// notebook.
+fn input_files(input: &Path) -> Result<Vec<PathBuf>> {
+    if input.extension().map_or(false, |ext| ext == "onetoc2") {
+        let notebook_dir = input.parent().wrap_err("Input file has no parent folder")?;
+        let mut files = Vec::new();
+        cache::collect_files(notebook_dir, &mut files)?;
+
+        Ok(files)
+    } else {
+        Ok(vec![input.to_path_buf()])
+    }
+}